@@ -1,24 +1,14 @@
 use anime::prelude::*;
-use palette::IntoColor;
-use std::str::FromStr;
-
-fn test() {
-    let x = palette::Srgb::from_str("s");
-}
 
 // Make sure we can compile all of the "tutorial" cases
 fn main() {
     // Animate CSS Properties
     animate("#target")
-        .left((240, Unit::Px))
-        .background_color(
-            palette::Srgb::from_str("#FFF")
-                .expect("Failed to interpret color")
-                .into_color(),
-        )
-        .border_radius((0, Unit::Percent), (50, Unit::Percent))
+        .left((240.0, Unit::Px))
+        .background_color(anime::css::parse_color("#FFF").expect("Failed to interpret color"))
+        .border_radius(Property::range((0.0, Unit::Percent), (50.0, Unit::Percent)))
         .easing(ease_in_out_quad())
-        .finish(); // TODO: palette from_str
+        .finish();
 
     // Animate CSS Transforms
     animate("#target")
@@ -97,7 +87,7 @@ fn main() {
 
     // Specific Unit
     animate("#target")
-        .width((100, Unit::Percent))
+        .width((100.0, Unit::Percent))
         .easing(ease_in_out_quad())
         .alternate()
         .repeat_forever()
@@ -108,7 +98,7 @@ fn main() {
         .keyframes(vec![
             AnimationKeyframe::new().props([
                 Property::translate_x(5),
-                Property::border_radius(10).delay(15),
+                Property::border_radius((10.0, Unit::Px)).delay(15),
             ]),
             AnimationKeyframe::new().props([Property::opacity(0.8)]),
         ])
@@ -0,0 +1,132 @@
+//! Animation-level keyframes, each animating several properties
+
+use super::{property::Parameters, Builder, ParameterValue};
+use crate::{easings::Easing, properties::Property};
+
+/// A property (of any type) with its own tween parameters, for use in an
+/// [`AnimationKeyframe`].
+///
+/// Created with the property constructors on
+/// [`Property`](super::property::Property), e.g. `Property::translate_x(250)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyframeProperty {
+    pub(crate) property: Property,
+    pub(crate) params: Parameters,
+}
+
+impl KeyframeProperty {
+    pub(crate) fn new(property: Property, params: Parameters) -> Self {
+        Self { property, params }
+    }
+
+    pub fn duration(mut self, value: u32) -> Self {
+        self.params.duration = Some(value);
+        self
+    }
+    pub fn delay(mut self, value: u32) -> Self {
+        self.params.delay = Some(value);
+        self
+    }
+    pub fn end_delay(mut self, value: u32) -> Self {
+        self.params.end_delay = Some(value);
+        self
+    }
+    pub fn easing(mut self, value: Easing) -> Self {
+        self.params.easing = Some(value);
+        self
+    }
+    pub fn round(mut self, value: u32) -> Self {
+        self.params.round = Some(value);
+        self
+    }
+}
+
+/// A step of an animation, animating several properties at once.
+///
+/// Parameters set on the keyframe apply to all of its properties, unless the
+/// property sets its own. If no duration is given, the animation's duration is
+/// split evenly between the keyframes. Alternatively, keyframes can be placed
+/// at an offset into the animation (see [`AnimationKeyframe::offset`])
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AnimationKeyframe {
+    props: Vec<KeyframeProperty>,
+    params: Parameters,
+    offset: Option<f32>,
+}
+
+impl AnimationKeyframe {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add properties to animate in this keyframe
+    pub fn props<I: IntoIterator<Item = KeyframeProperty>>(mut self, props: I) -> Self {
+        self.props.extend(props);
+        self
+    }
+
+    /// Place the keyframe at a fraction (`0.0` to `1.0`) of the animation's
+    /// duration, like a percentage in CSS `@keyframes` (see
+    /// [`PropertyKeyframe::offset`](crate::properties::PropertyKeyframe::offset))
+    pub fn offset(mut self, offset: f32) -> Self {
+        self.offset = Some(offset.clamp(0.0, 1.0));
+        self
+    }
+
+    pub fn duration(mut self, value: u32) -> Self {
+        self.params.duration = Some(value);
+        self
+    }
+    pub fn delay(mut self, value: u32) -> Self {
+        self.params.delay = Some(value);
+        self
+    }
+    pub fn end_delay(mut self, value: u32) -> Self {
+        self.params.end_delay = Some(value);
+        self
+    }
+    pub fn easing(mut self, value: Easing) -> Self {
+        self.params.easing = Some(value);
+        self
+    }
+    pub fn round(mut self, value: u32) -> Self {
+        self.params.round = Some(value);
+        self
+    }
+}
+
+impl Builder {
+    /// Animate through a sequence of keyframes.
+    ///
+    /// The keyframes are flattened into a chain of tweens for each property.
+    /// Properties missing from a keyframe hold their previous value for it
+    pub fn keyframes(&mut self, keyframes: Vec<AnimationKeyframe>) -> &mut Self {
+        // One chain per property, in the order they first appear
+        let mut chains: Vec<Property> = Vec::new();
+        for prop in keyframes.iter().flat_map(|frame| frame.props.iter()) {
+            if !chains.iter().any(|chain| chain.same_kind(&prop.property)) {
+                chains.push(prop.property.empty_keyframes());
+            }
+        }
+
+        for mut chain in chains {
+            for frame in keyframes.iter() {
+                match frame
+                    .props
+                    .iter()
+                    .find(|prop| chain.same_kind(&prop.property))
+                {
+                    Some(prop) => chain.push_keyframe(
+                        Some(&prop.property),
+                        &prop.params.or(&frame.params),
+                        frame.offset,
+                    ),
+                    None => chain.push_keyframe(None, &frame.params, frame.offset),
+                }
+            }
+            self.properties
+                .push(ParameterValue::Value((chain, Parameters::default())));
+        }
+        self
+    }
+}
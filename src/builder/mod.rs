@@ -1,32 +1,34 @@
 //! Public interface for building Animations
 
-use std::{
-    collections::{HashMap, HashSet},
-    fmt::Debug,
-    sync::Arc,
-};
+use std::{rc::Rc, sync::Arc};
+
+use wasm_bindgen::UnwrapThrowExt;
 
+use self::property::{Parameters, TweenParameters};
 use crate::{
     easings::Easing,
-    engine::{Animation, Engine, Handle, Instance},
-    properties::{CssProperty, Property, PropertyValue, Unit, Value},
+    engine::{Animatable, Callbacks, Engine, EngineError, Handle, Instance, Speed},
+    properties::Property,
     target::Target,
     Direction, Repeat,
 };
 
-use self::property::Parameters;
-
+pub(crate) mod keyframe;
 pub(crate) mod property;
 
-pub trait AnimationCallback: 'static + Debug + Fn(&Animation) {} // TODO: closure type
-impl<T> AnimationCallback for T where T: 'static + Debug + Fn(&Animation) {}
+/// Called with the animation instance on playback events.
+///
+/// Callbacks run while the engine is locked, so they must not (synchronously)
+/// call back into the engine
+pub trait AnimationCallback: 'static + Fn(&Instance) {}
+impl<T> AnimationCallback for T where T: 'static + Fn(&Instance) {}
 
 // TODO: option<Arc<dyn>> callbacks for Clone?
 pub struct Builder {
     engine: Engine,
     target: Arc<Target>,
 
-    pub(crate) properties: Vec<(Property, Parameters)>, // TODO: hashmap? of enum discriminant?
+    pub(crate) properties: Vec<ParameterValue<(Property, Parameters)>>, // TODO: hashmap? of enum discriminant?
 
     duration: Option<ParameterValue<u32>>,
     delay: Option<ParameterValue<u32>>,
@@ -35,6 +37,7 @@ pub struct Builder {
     easing: Option<Easing>,
     direction: Option<Direction>,
     repeat: Option<Repeat>,
+    speed: Option<Speed>,
 
     on_update: Option<Box<dyn AnimationCallback>>,
 
@@ -49,22 +52,17 @@ pub struct Builder {
     on_change_complete: Option<Box<dyn AnimationCallback>>,
 }
 
-// pub struct Keyframe<T> {
-//     props: HashMap<Property, PropertyValue<T>>,
-//     // frame params
-// }
-
 impl Engine {
     pub fn animate<T: Into<Target>>(&self, target: T) -> Builder {
-        Builder::new(self.clone(), target.into())
+        Builder::new(self.clone(), Arc::new(target.into()))
     }
 }
 
 impl Builder {
-    fn new(engine: Engine, target: Target) -> Self {
+    fn new(engine: Engine, target: Arc<Target>) -> Self {
         Self {
             engine,
-            target: Arc::new(target),
+            target,
 
             properties: Vec::new(),
 
@@ -75,6 +73,7 @@ impl Builder {
             easing: None,
             direction: None,
             repeat: None,
+            speed: None,
 
             on_update: None,
             on_begin: None,
@@ -93,29 +92,124 @@ impl Builder {
     //     self
     // }
 
-    // pub fn keyframes(&mut self, keyframes: Vec<Keyframe>) -> &mut Self {
-    //     for kf in keyframes {
-    //         todo!() // TODO: flatten to props
-    //     }
-    // }
-
     // pub fn property(&mut self, property: Property, value: PropertyValue) -> &mut Self {
     //     self.properties
     // }
 
-    // TODO: just return an animation
-    pub fn finish(self) -> Handle {
-        let Builder { engine, target, .. } = self;
-        // engine.add(target, options);
+    /// Build the animation and add it to the engine, returning a handle to
+    /// control it.
+    ///
+    /// The builder is left empty (targeting the same elements) afterwards.
+    ///
+    /// # Panics
+    ///
+    /// If the targets can't be resolved or the engine can't be locked. See
+    /// [`Builder::try_finish`]
+    pub fn finish(&mut self) -> Handle {
+        self.try_finish()
+            .expect_throw("Failed to add animation to the engine")
+    }
+
+    /// Build the animation and add it to the engine, returning a handle to
+    /// control it
+    pub fn try_finish(&mut self) -> Result<Handle, EngineError> {
+        let builder = self.take();
+        let engine = builder.engine.clone();
+        engine.add(builder.build(&TweenParameters::default(), true)?)
+    }
+
+    /// Take the built up animation, leaving an empty builder (for the same
+    /// targets) in its place
+    pub(crate) fn take(&mut self) -> Builder {
+        std::mem::replace(self, Builder::new(self.engine.clone(), self.target.clone()))
+    }
+
+    /// Resolve the properties for each target into an instance, using
+    /// `defaults` for any parameters that aren't set
+    pub(crate) fn build(
+        self,
+        defaults: &TweenParameters,
+        autoplay: bool,
+    ) -> Result<Instance, EngineError> {
+        let elements = self.target.list()?;
+        let total = elements.len();
+
+        let mut animations = Vec::with_capacity(total * self.properties.len());
+        for (index, element) in elements.into_iter().enumerate() {
+            let target_defaults = TweenParameters {
+                duration: ParameterValue::resolve(&self.duration, &self.target, index, total)
+                    .map(|d| d as f32)
+                    .unwrap_or(defaults.duration),
+                delay: ParameterValue::resolve(&self.delay, &self.target, index, total)
+                    .map(|d| d as f32)
+                    .unwrap_or(defaults.delay),
+                end_delay: ParameterValue::resolve(&self.end_delay, &self.target, index, total)
+                    .map(|d| d as f32)
+                    .unwrap_or(defaults.end_delay),
+                easing: self
+                    .easing
+                    .clone()
+                    .unwrap_or_else(|| defaults.easing.clone()),
+                round: defaults.round,
+                color_space: defaults.color_space,
+                rotation: defaults.rotation,
+            };
+
+            let animatable = Rc::new(Animatable::new(Rc::new(element)));
+            for property in self.properties.iter() {
+                let (property, params) = property.get(&self.target, index, total);
+                animations.push(
+                    property.animation(Some(animatable.clone()), &params.resolve(&target_defaults)),
+                );
+            }
+        }
 
-        todo!()
+        let callbacks = Callbacks {
+            update: self.on_update,
+            begin: self.on_begin,
+            complete: self.on_complete,
+            loop_begin: self.on_loop_begin,
+            loop_complete: self.on_loop_complete,
+            change: self.on_change,
+            change_begin: self.on_change_begin,
+            change_complete: self.on_change_complete,
+        };
+
+        let mut instance = Instance::new(
+            animations,
+            self.direction.unwrap_or(Direction::Normal),
+            self.repeat.unwrap_or_default(),
+            autoplay,
+            callbacks,
+            defaults,
+        );
+        instance.set_speed(self.speed.unwrap_or(Speed::Normal));
+        Ok(instance)
     }
 }
 
+/// Computes a value for the target at `index` of `total`
+pub(crate) type ComputeFn<T> = Box<dyn Fn(&Target, usize, usize) -> T>;
+
 // TODO: combine with PropertyValue?
-enum ParameterValue<T> {
+pub(crate) enum ParameterValue<T> {
     Value(T),
-    Computed(Box<dyn Fn(&Target, usize, usize) -> T>),
+    Computed(ComputeFn<T>),
+}
+
+impl<T: Clone> ParameterValue<T> {
+    /// Get the value for the target at `index` of `total`
+    pub(crate) fn get(&self, target: &Target, index: usize, total: usize) -> T {
+        match self {
+            ParameterValue::Value(v) => v.clone(),
+            ParameterValue::Computed(f) => f(target, index, total),
+        }
+    }
+
+    /// Get the value (if set) for the target at `index` of `total`
+    fn resolve(value: &Option<Self>, target: &Target, index: usize, total: usize) -> Option<T> {
+        value.as_ref().map(|v| v.get(target, index, total))
+    }
 }
 
 // use crate::properties::Color;
@@ -125,8 +219,8 @@ impl Builder {
         self
     }
 
-    pub fn duration<T: Into<u32>>(&mut self, value: T) -> &mut Self {
-        self.duration = Some(ParameterValue::Value(value.into()));
+    pub fn duration(&mut self, value: u32) -> &mut Self {
+        self.duration = Some(ParameterValue::Value(value));
         self
     }
 
@@ -140,8 +234,8 @@ impl Builder {
         self
     }
 
-    pub fn delay<T: Into<u32>>(&mut self, value: T) -> &mut Self {
-        self.delay = Some(ParameterValue::Value(value.into()));
+    pub fn delay(&mut self, value: u32) -> &mut Self {
+        self.delay = Some(ParameterValue::Value(value));
         self
     }
 
@@ -155,8 +249,8 @@ impl Builder {
         self
     }
 
-    pub fn end_delay<T: Into<u32>>(&mut self, value: T) -> &mut Self {
-        self.end_delay = Some(ParameterValue::Value(value.into()));
+    pub fn end_delay(&mut self, value: u32) -> &mut Self {
+        self.end_delay = Some(ParameterValue::Value(value));
         self
     }
 
@@ -170,6 +264,17 @@ impl Builder {
         self
     }
 
+    /// Playback speed, relative to the parent timeline (or the engine)
+    pub fn speed(&mut self, speed: Speed) -> &mut Self {
+        self.speed = Some(speed);
+        self
+    }
+
+    pub fn direction(&mut self, direction: Direction) -> &mut Self {
+        self.direction = Some(direction);
+        self
+    }
+
     pub fn alternate(&mut self) -> &mut Self {
         self.direction = Some(Direction::Alternate);
         self
@@ -188,3 +293,54 @@ impl Builder {
         self
     }
 }
+
+// Callbacks
+impl Builder {
+    /// Called on every frame while the animation is playing (after it begins)
+    pub fn on_update<F: AnimationCallback>(&mut self, f: F) -> &mut Self {
+        self.on_update = Some(Box::new(f));
+        self
+    }
+
+    /// Called once, when the animation starts playing
+    pub fn on_begin<F: AnimationCallback>(&mut self, f: F) -> &mut Self {
+        self.on_begin = Some(Box::new(f));
+        self
+    }
+
+    /// Called once, when the animation (including all repetitions) completes
+    pub fn on_complete<F: AnimationCallback>(&mut self, f: F) -> &mut Self {
+        self.on_complete = Some(Box::new(f));
+        self
+    }
+
+    /// Called at the start of every repetition
+    pub fn on_loop_begin<F: AnimationCallback>(&mut self, f: F) -> &mut Self {
+        self.on_loop_begin = Some(Box::new(f));
+        self
+    }
+
+    /// Called at the end of every repetition
+    pub fn on_loop_complete<F: AnimationCallback>(&mut self, f: F) -> &mut Self {
+        self.on_loop_complete = Some(Box::new(f));
+        self
+    }
+
+    /// Called on every frame where values are changing (outside of delays)
+    pub fn on_change<F: AnimationCallback>(&mut self, f: F) -> &mut Self {
+        self.on_change = Some(Box::new(f));
+        self
+    }
+
+    /// Called when values start changing (after the delay)
+    pub fn on_change_begin<F: AnimationCallback>(&mut self, f: F) -> &mut Self {
+        self.on_change_begin = Some(Box::new(f));
+        self
+    }
+
+    /// Called when values stop changing (before the end delay)
+    pub fn on_change_complete<F: AnimationCallback>(&mut self, f: F) -> &mut Self {
+        self.on_change_complete = Some(Box::new(f));
+        self
+    }
+}
@@ -1,6 +1,10 @@
 use crate::{
-    easings::Easing,
-    properties::{PropertyValue, Value},
+    easings::{helpers::elastic_out, Easing},
+    engine::Tween,
+    properties::{
+        Angle, Color, ColorSpace, Interpolation, KeyframeValue, PropertyKeyframe, PropertyValue,
+        RotationMode, Tweenable, Unit, Value, ValueOp,
+    },
 };
 
 // use super::Parameters;
@@ -11,13 +15,257 @@ pub struct Property<T> {
     pub(crate) params: Parameters,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct Parameters {
     pub duration: Option<u32>, // default 1000
     pub delay: Option<u32>,
     pub end_delay: Option<u32>,
     pub easing: Option<Easing>,
     pub round: Option<u32>,
+    pub color_space: Option<ColorSpace>, // default sRGB
+    pub rotation: Option<RotationMode>,
+}
+
+impl Parameters {
+    /// Fill in any unset parameters from `other`
+    pub(crate) fn or(&self, other: &Parameters) -> Parameters {
+        Parameters {
+            duration: self.duration.or(other.duration),
+            delay: self.delay.or(other.delay),
+            end_delay: self.end_delay.or(other.end_delay),
+            easing: self.easing.clone().or_else(|| other.easing.clone()),
+            round: self.round.or(other.round),
+            color_space: self.color_space.or(other.color_space),
+            rotation: self.rotation.or(other.rotation),
+        }
+    }
+
+    /// Fill in any unset parameters from `defaults`
+    pub(crate) fn resolve(&self, defaults: &TweenParameters) -> TweenParameters {
+        TweenParameters {
+            duration: self.duration.map(|d| d as f32).unwrap_or(defaults.duration),
+            delay: self.delay.map(|d| d as f32).unwrap_or(defaults.delay),
+            end_delay: self
+                .end_delay
+                .map(|d| d as f32)
+                .unwrap_or(defaults.end_delay),
+            easing: self
+                .easing
+                .clone()
+                .unwrap_or_else(|| defaults.easing.clone()),
+            round: self.round.unwrap_or(defaults.round),
+            color_space: self.color_space.unwrap_or(defaults.color_space),
+            rotation: self.rotation.or(defaults.rotation),
+        }
+    }
+}
+
+/// Fully resolved tween parameters, for a single property on a single target
+#[derive(Debug, Clone)]
+pub(crate) struct TweenParameters {
+    pub duration: f32,
+    pub delay: f32,
+    pub end_delay: f32,
+    pub easing: Easing,
+    pub round: u32,
+    pub color_space: ColorSpace,
+    pub rotation: Option<RotationMode>,
+}
+
+impl Default for TweenParameters {
+    fn default() -> Self {
+        Self {
+            duration: 1000.0,
+            delay: 0.0,
+            end_delay: 0.0,
+            easing: elastic_out(1.0, 0.5),
+            round: 0,
+            color_space: ColorSpace::Srgb,
+            rotation: None,
+        }
+    }
+}
+
+/// Build the chain of tweens for a property value.
+///
+/// `initial` is the value to start from if one isn't given (and that relative
+/// values are relative to), and `default_unit` is used for any values given
+/// without a unit. When a tween's start and end units differ, the start is
+/// converted to the end's unit using `factor` (see [`Unit::factor`]), or the
+/// end to the start's if that isn't possible
+pub(crate) fn tweens<T: Tweenable>(
+    value: &PropertyValue<T>,
+    params: &TweenParameters,
+    initial: Value<T>,
+    default_unit: Option<Unit>,
+    factor: &dyn Fn(Unit, Unit) -> Option<f32>,
+) -> Vec<Tween<T>> {
+    let with_unit = |value: Value<T>| {
+        let unit = value.unit().or(default_unit);
+        Value::new(value.value().clone(), unit)
+    };
+    // The value in `unit` (as is, if either doesn't have a unit), or `None` if
+    // it can't be converted
+    let convert = |value: &Value<T>, unit: Option<Unit>| match (value.unit(), unit) {
+        (Some(from), Some(to)) if from != to => value.convert_with(to, factor),
+        _ => Some(value.clone()),
+    };
+    let tween = |from: Value<T>, to: Value<T>, start: f32, params: &TweenParameters| {
+        let (from, to) = (with_unit(from), with_unit(to));
+        // Values that can't be converted either way have nothing to tween
+        // between, so jump to the end value
+        let (from, to) = match (convert(&from, to.unit()), convert(&to, from.unit())) {
+            (Some(from), _) => (from, to),
+            (None, Some(to)) => (from, to),
+            (None, None) => (to.clone(), to),
+        };
+        Tween {
+            from,
+            to,
+            start,
+            end: start + params.delay + params.duration + params.end_delay,
+            duration: params.duration,
+            delay: params.delay,
+            end_delay: params.end_delay,
+            easing: params.easing.clone(),
+            round: params.round,
+            interpolation: Interpolation {
+                color_space: params.color_space,
+                rotation: params.rotation,
+            },
+        }
+    };
+
+    // The start and end of a value relative to `current`, combined in the
+    // operand's unit (e.g. `"+=10%"` of a value in px) or else the current
+    // value's. Values that can't be combined hold the current value
+    let relative = |current: Value<T>, op: &ValueOp<T>| {
+        let current = with_unit(current);
+        if let Some(converted) = convert(&current, op.operand().unit()) {
+            let to = op.resolve(&converted);
+            (converted, to)
+        } else if let Some(operand) = convert(op.operand(), current.unit()) {
+            let to = op.with_operand(operand).resolve(&current);
+            (current, to)
+        } else {
+            (current.clone(), current)
+        }
+    };
+    // The start and end of a keyframe following `previous`
+    let keyframe_values = |previous: Value<T>, value: &Option<KeyframeValue<T>>| match value {
+        None => (previous.clone(), previous),
+        Some(KeyframeValue::Exact(to)) => (previous, to.clone()),
+        Some(KeyframeValue::Range(from, to)) => (from.clone(), to.clone()),
+        Some(KeyframeValue::Relative(op)) => relative(previous, op),
+    };
+
+    match value {
+        PropertyValue::Exact(to) => vec![tween(initial, to.clone(), 0.0, params)],
+        PropertyValue::Range(from, to) => vec![tween(from.clone(), to.clone(), 0.0, params)],
+
+        PropertyValue::Relative(op) => {
+            let (from, to) = relative(initial, op);
+            vec![tween(from, to, 0.0, params)]
+        }
+
+        PropertyValue::Keyframes(keyframes) if keyframes.iter().any(|k| k.offset.is_some()) => {
+            // Keyframes are placed by offset into the duration, each tweening
+            // from the previous one, and holding the last value until the end
+            let mut placed: Vec<_> = offsets(keyframes).into_iter().zip(keyframes).collect();
+            placed.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+            let n = placed.len();
+            let mut tweens: Vec<Tween<T>> = Vec::with_capacity(n);
+            let mut previous_offset = 0.0;
+            for (i, (offset, keyframe)) in placed.into_iter().enumerate() {
+                let params = TweenParameters {
+                    duration: (offset - previous_offset) * params.duration,
+                    delay: if i == 0 { params.delay } else { 0.0 },
+                    end_delay: if i == n - 1 {
+                        (1.0 - offset) * params.duration + params.end_delay
+                    } else {
+                        0.0
+                    },
+                    easing: keyframe
+                        .params
+                        .easing
+                        .clone()
+                        .unwrap_or_else(|| params.easing.clone()),
+                    round: keyframe.params.round.unwrap_or(params.round),
+                    color_space: keyframe.params.color_space.unwrap_or(params.color_space),
+                    rotation: keyframe.params.rotation.or(params.rotation),
+                };
+                let (previous, start) = match tweens.last() {
+                    Some(previous) => (previous.to.clone(), previous.end),
+                    None => (initial.clone(), 0.0),
+                };
+                let (from, to) = keyframe_values(previous, &keyframe.value);
+                tweens.push(tween(from, to, start, &params));
+                previous_offset = offset;
+            }
+            tweens
+        }
+
+        PropertyValue::Keyframes(keyframes) => {
+            // Unless the keyframe sets its own, duration is split between the
+            // keyframes, the delay only applies to the first and the end delay
+            // only to the last
+            let n = keyframes.len();
+            let mut tweens: Vec<Tween<T>> = Vec::with_capacity(n);
+            for (i, keyframe) in keyframes.iter().enumerate() {
+                let params = keyframe.params.resolve(&TweenParameters {
+                    duration: params.duration / n as f32,
+                    delay: if i == 0 { params.delay } else { 0.0 },
+                    end_delay: if i == n - 1 { params.end_delay } else { 0.0 },
+                    ..params.clone()
+                });
+                let (previous, start) = match tweens.last() {
+                    Some(previous) => (previous.to.clone(), previous.end),
+                    None => (initial.clone(), 0.0),
+                };
+                let (from, to) = keyframe_values(previous, &keyframe.value);
+                tweens.push(tween(from, to, start, &params));
+            }
+            tweens
+        }
+    }
+}
+
+/// Offsets of keyframes into the duration, filling in any that weren't given:
+/// the first defaults to `0.0`, the last to `1.0` and the rest are spaced
+/// evenly between their neighbours (as in the Web Animations API)
+fn offsets<T>(keyframes: &[PropertyKeyframe<T>]) -> Vec<f32> {
+    let n = keyframes.len();
+    let mut offsets: Vec<Option<f32>> = keyframes.iter().map(|k| k.offset).collect();
+    if n > 1 && offsets[n - 1].is_none() {
+        offsets[n - 1] = Some(1.0);
+    }
+    if n > 0 && offsets[0].is_none() {
+        offsets[0] = Some(if n == 1 { 1.0 } else { 0.0 });
+    }
+
+    let mut resolved = Vec::with_capacity(n);
+    let mut i = 0;
+    while i < n {
+        match offsets[i] {
+            Some(offset) => {
+                resolved.push(offset);
+                i += 1;
+            }
+            None => {
+                // Space the run of missing offsets between the known ones
+                let before = resolved.last().copied().unwrap_or(0.0);
+                let end = (i..n).find(|&j| offsets[j].is_some()).unwrap_or(n);
+                let after = offsets.get(end).copied().flatten().unwrap_or(1.0);
+                let steps = (end - i + 1) as f32;
+                for j in i..end {
+                    resolved.push(before + (after - before) * (j - i + 1) as f32 / steps);
+                }
+                i = end;
+            }
+        }
+    }
+    resolved
 }
 
 impl<T> Property<T> {
@@ -35,6 +283,23 @@ impl<T> Property<T> {
         }
     }
 
+    /// Animate relative to the target's value when the animation starts (e.g.
+    /// `Property::relative(ValueOp::Add((100.0, Unit::Px).into()))` for `"+=100px"`)
+    pub fn relative(op: ValueOp<T>) -> Self {
+        Self {
+            value: PropertyValue::Relative(op),
+            params: Parameters::default(),
+        }
+    }
+
+    /// Animate through a sequence of keyframes
+    pub fn keyframes(keyframes: Vec<PropertyKeyframe<T>>) -> Self {
+        Self {
+            value: PropertyValue::Keyframes(keyframes),
+            params: Parameters::default(),
+        }
+    }
+
     pub fn duration(mut self, value: u32) -> Self {
         self.params.duration = Some(value);
         self
@@ -57,12 +322,63 @@ impl<T> Property<T> {
     }
 }
 
+impl Property<Angle> {
+    /// Which way the angle rotates (e.g.
+    /// `Property::value(Angle::Turns(1.5)).rotation(RotationMode::Shortest)`),
+    /// literally from one value to the other if not set
+    pub fn rotation(mut self, value: RotationMode) -> Self {
+        self.params.rotation = Some(value);
+        self
+    }
+}
+
+impl Property<Color> {
+    /// The color space to interpolate in (e.g.
+    /// `Property::value(color).color_space(ColorSpace::Oklab)`), sRGB if not set
+    pub fn color_space(mut self, value: ColorSpace) -> Self {
+        self.params.color_space = Some(value);
+        self
+    }
+
+    /// Which way hues go around (e.g.
+    /// `Property::value(color).rotation(RotationMode::Clockwise)`).
+    ///
+    /// Takes precedence over the hue direction of the color space (see
+    /// [`ColorSpace::Hsl`] and [`ColorSpace::Lch`]), and has no effect in color
+    /// spaces without a hue
+    pub fn rotation(mut self, value: RotationMode) -> Self {
+        self.params.rotation = Some(value);
+        self
+    }
+}
+
 // impl<T> From<Property<T>> for Value<T> {
 //     fn from(p: Property<T>) -> Self {
 //         p.value
 //     }
 // }
 
+impl<T> From<PropertyValue<T>> for Property<T> {
+    fn from(value: PropertyValue<T>) -> Self {
+        Self {
+            value,
+            params: Parameters::default(),
+        }
+    }
+}
+
+impl<T> From<ValueOp<T>> for Property<T> {
+    fn from(op: ValueOp<T>) -> Self {
+        Property::relative(op)
+    }
+}
+
+impl<T> From<Vec<PropertyKeyframe<T>>> for Property<T> {
+    fn from(keyframes: Vec<PropertyKeyframe<T>>) -> Self {
+        Property::keyframes(keyframes)
+    }
+}
+
 impl<T: Into<Value<U>>, U> From<T> for Property<U> {
     fn from(t: T) -> Self {
         Self {
@@ -71,3 +387,257 @@ impl<T: Into<Value<U>>, U> From<T> for Property<U> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The start and end of each tween for `value`, starting from `initial`
+    fn tween_values(
+        value: PropertyValue<f32>,
+        initial: &str,
+        factor: &dyn Fn(Unit, Unit) -> Option<f32>,
+    ) -> Vec<(String, String)> {
+        let initial = initial.parse().unwrap();
+        tweens(&value, &TweenParameters::default(), initial, None, factor)
+            .iter()
+            .map(|tween| (tween.from.to_string(), tween.to.to_string()))
+            .collect()
+    }
+
+    fn pair(from: &str, to: &str) -> (String, String) {
+        (from.to_string(), to.to_string())
+    }
+
+    /// Converts % to px as if the parent were 200px wide
+    fn percent_factor(from: Unit, to: Unit) -> Option<f32> {
+        match (from, to) {
+            (Unit::Px, Unit::Percent) => Some(0.5),
+            (Unit::Percent, Unit::Px) => Some(2.0),
+            _ => Unit::factor(from, to),
+        }
+    }
+
+    #[test]
+    fn resolves_relative_values() {
+        let relative = |s: &str| s.parse::<PropertyValue<f32>>().unwrap();
+        assert_eq!(
+            tween_values(relative("+=50px"), "100px", &Unit::factor),
+            [pair("100px", "150px")]
+        );
+        assert_eq!(
+            tween_values(relative("-=1in"), "192px", &Unit::factor),
+            [pair("2in", "1in")]
+        );
+        assert_eq!(
+            tween_values(relative("*=2"), "100px", &Unit::factor),
+            [pair("100px", "200px")]
+        );
+        assert_eq!(
+            tween_values(relative("+=10%"), "100px", &percent_factor),
+            [pair("50%", "60%")]
+        );
+    }
+
+    #[test]
+    fn keeps_units_that_cant_be_converted() {
+        let relative = "+=10%".parse::<PropertyValue<f32>>().unwrap();
+        assert_eq!(
+            tween_values(relative, "100px", &Unit::factor),
+            [pair("100px", "100px")]
+        );
+
+        // The end is converted to the start's unit instead
+        let from_percent = |from: Unit, to: Unit| match (from, to) {
+            (Unit::Percent, Unit::Px) => Some(2.0),
+            _ => None,
+        };
+        let range = PropertyValue::Range((10.0, Unit::Px).into(), (50.0, Unit::Percent).into());
+        assert_eq!(
+            tween_values(range.clone(), "0", &from_percent),
+            [pair("10px", "100px")]
+        );
+        // Or jumps to the end, if neither can be converted
+        assert_eq!(
+            tween_values(range, "0", &Unit::factor),
+            [pair("50%", "50%")]
+        );
+    }
+
+    #[test]
+    fn resolves_keyframes_against_the_previous_keyframe() {
+        let keyframes = PropertyValue::Keyframes(vec![
+            PropertyKeyframe::new().value((100.0, Unit::Px)),
+            PropertyKeyframe::new().relative(ValueOp::Add((50.0, Unit::Px).into())),
+            PropertyKeyframe::new(),
+            PropertyKeyframe::new().range((0.0, Unit::Px), (10.0, Unit::Px)),
+            PropertyKeyframe::new().relative(ValueOp::Multiply(3.0.into())),
+        ]);
+        assert_eq!(
+            tween_values(keyframes, "20px", &Unit::factor),
+            [
+                pair("20px", "100px"),
+                pair("100px", "150px"),
+                pair("150px", "150px"),
+                pair("0px", "10px"),
+                pair("10px", "30px"),
+            ]
+        );
+    }
+
+    /// The (start, delay, duration, end delay, end) of each tween for
+    /// `keyframes`, with a duration of 1000, delay of 100 and end delay of 200
+    fn keyframe_timings(keyframes: Vec<PropertyKeyframe<f32>>) -> Vec<[f32; 5]> {
+        let params = TweenParameters {
+            delay: 100.0,
+            end_delay: 200.0,
+            ..TweenParameters::default()
+        };
+        let value = PropertyValue::Keyframes(keyframes);
+        tweens(&value, &params, 0.0.into(), None, &Unit::factor)
+            .iter()
+            .map(|t| [t.start, t.delay, t.duration, t.end_delay, t.end])
+            .collect()
+    }
+
+    #[test]
+    fn splits_the_duration_between_keyframes() {
+        let keyframes = (1..=4)
+            .map(|i| PropertyKeyframe::new().value(i as f32))
+            .collect();
+        // Only the first is delayed, and only the last has an end delay
+        assert_eq!(
+            keyframe_timings(keyframes),
+            [
+                [0.0, 100.0, 250.0, 0.0, 350.0],
+                [350.0, 0.0, 250.0, 0.0, 600.0],
+                [600.0, 0.0, 250.0, 0.0, 850.0],
+                [850.0, 0.0, 250.0, 200.0, 1300.0],
+            ]
+        );
+    }
+
+    #[test]
+    fn keyframes_override_the_property_parameters() {
+        let keyframes = vec![
+            PropertyKeyframe::new().value(1.0).delay(0),
+            PropertyKeyframe::new()
+                .value(2.0)
+                .duration(100)
+                .delay(50)
+                .easing(Easing::Linear),
+            PropertyKeyframe::new().value(3.0).end_delay(0),
+        ];
+        assert_eq!(
+            keyframe_timings(keyframes.clone()),
+            [
+                [0.0, 0.0, 1000.0 / 3.0, 0.0, 1000.0 / 3.0],
+                [1000.0 / 3.0, 50.0, 100.0, 0.0, 1000.0 / 3.0 + 150.0],
+                [
+                    1000.0 / 3.0 + 150.0,
+                    0.0,
+                    1000.0 / 3.0,
+                    0.0,
+                    2000.0 / 3.0 + 150.0
+                ],
+            ]
+        );
+
+        let value = PropertyValue::Keyframes(keyframes);
+        let params = TweenParameters::default();
+        let easings: Vec<_> = tweens(&value, &params, 0.0.into(), None, &Unit::factor)
+            .into_iter()
+            .map(|tween| tween.easing)
+            .collect();
+        assert_eq!(
+            easings,
+            [params.easing.clone(), Easing::Linear, params.easing]
+        );
+    }
+
+    #[test]
+    fn fills_in_missing_offsets() {
+        let at = |offsets: &[Option<f32>]| {
+            let keyframes: Vec<PropertyKeyframe<f32>> = offsets
+                .iter()
+                .map(|offset| PropertyKeyframe {
+                    offset: *offset,
+                    ..PropertyKeyframe::new()
+                })
+                .collect();
+            super::offsets(&keyframes)
+        };
+        assert_eq!(at(&[None]), [1.0]);
+        assert_eq!(at(&[None; 5]), [0.0, 0.25, 0.5, 0.75, 1.0]);
+        assert_eq!(
+            at(&[None, Some(0.25), None, Some(0.75), None]),
+            [0.0, 0.25, 0.5, 0.75, 1.0]
+        );
+        assert_eq!(at(&[Some(0.5), None, None]), [0.5, 0.75, 1.0]);
+    }
+
+    #[test]
+    fn places_keyframes_by_offset() {
+        let keyframes = vec![
+            PropertyKeyframe::new().value(1.0),
+            PropertyKeyframe::new().value(2.0).offset(0.25),
+            PropertyKeyframe::new().value(3.0).duration(10),
+        ];
+        // Durations come from the offsets, holding the first value until its
+        // offset and ignoring the keyframes' own durations
+        assert_eq!(
+            keyframe_timings(keyframes),
+            [
+                [0.0, 100.0, 0.0, 0.0, 100.0],
+                [100.0, 0.0, 250.0, 0.0, 350.0],
+                [350.0, 0.0, 750.0, 200.0, 1300.0],
+            ]
+        );
+    }
+
+    #[test]
+    fn flattens_animation_keyframes_into_property_chains() {
+        use crate::{
+            builder::{keyframe::AnimationKeyframe, ParameterValue},
+            engine::EngineBuilder,
+            properties::{CssProperty, CssTransform, Property as Prop},
+        };
+
+        let engine = EngineBuilder::manual().init().unwrap();
+        let mut builder = engine.animate("#a");
+        builder.keyframes(vec![
+            AnimationKeyframe::new()
+                .props([
+                    Property::translate_x(100),
+                    Property::opacity(0.5).duration(300),
+                ])
+                .duration(200),
+            AnimationKeyframe::new()
+                .props([Property::translate_x(200)])
+                .offset(0.5),
+        ]);
+
+        let chains: Vec<_> = builder
+            .properties
+            .iter()
+            .map(|property| match property {
+                ParameterValue::Value((property, _)) => property.clone(),
+                ParameterValue::Computed(_) => panic!("keyframes aren't computed"),
+            })
+            .collect();
+        assert_eq!(
+            chains,
+            [
+                Prop::CssTransform(CssTransform::TranslateX(PropertyValue::Keyframes(vec![
+                    PropertyKeyframe::new().value(100).duration(200),
+                    PropertyKeyframe::new().value(200).offset(0.5),
+                ]))),
+                Prop::CssProperty(CssProperty::Opacity(PropertyValue::Keyframes(vec![
+                    PropertyKeyframe::new().value(0.5).duration(300),
+                    // Holding the previous value
+                    PropertyKeyframe::new().offset(0.5),
+                ]))),
+            ]
+        );
+    }
+}
@@ -0,0 +1,210 @@
+//! CSS colors, as given by users or read from computed styles
+//!
+//! See https://developer.mozilla.org/en-US/docs/Web/CSS/color_value
+
+use palette::{FromColor, Hsl, Srgb};
+
+use crate::properties::Color;
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("invalid color: {0:?}")]
+pub struct ColorParseError(String);
+
+/// Parse a CSS color: hex (`#rgb`, `#rgba`, `#rrggbb` or `#rrggbbaa`),
+/// `rgb()`/`rgba()`, `hsl()`/`hsla()` (with either comma or space separated
+/// arguments) or a named color
+pub fn parse_color(text: &str) -> Result<Color, ColorParseError> {
+    let error = || ColorParseError(text.to_string());
+    let s = text.trim().to_ascii_lowercase();
+
+    if let Some(hex) = s.strip_prefix('#') {
+        return parse_hex(hex).ok_or_else(error);
+    }
+
+    if let Some(open) = s.find('(') {
+        let args = s[open + 1..].strip_suffix(')').ok_or_else(error)?;
+        let args = arguments(args);
+        return match s[..open].trim() {
+            "rgb" | "rgba" => parse_rgb(&args),
+            "hsl" | "hsla" => parse_hsl(&args),
+            _ => None,
+        }
+        .ok_or_else(error);
+    }
+
+    if s == "transparent" {
+        return Ok(Color::new(0, 0, 0, 0.0));
+    }
+    palette::named::from_str(&s)
+        .map(|color| Color::new(color.red, color.green, color.blue, 1.0))
+        .ok_or_else(error)
+}
+
+fn parse_hex(hex: &str) -> Option<Color> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digit = |i: usize| u8::from_str_radix(&hex[i..=i], 16).ok().map(|d| d * 17);
+    let pair = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+
+    let (red, green, blue, alpha) = match hex.len() {
+        3 => (digit(0)?, digit(1)?, digit(2)?, 255),
+        4 => (digit(0)?, digit(1)?, digit(2)?, digit(3)?),
+        6 => (pair(0)?, pair(2)?, pair(4)?, 255),
+        8 => (pair(0)?, pair(2)?, pair(4)?, pair(6)?),
+        _ => return None,
+    };
+    Some(Color::new(red, green, blue, alpha as f32 / 255.0))
+}
+
+/// Split function arguments, separated by commas or spaces (with an optional
+/// `/` before the alpha)
+fn arguments(args: &str) -> Vec<&str> {
+    args.split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+        .filter(|arg| !arg.is_empty())
+        .collect()
+}
+
+/// A number, or a percentage of `percent`
+fn number(arg: &str, percent: f32) -> Option<f32> {
+    match arg {
+        "none" => Some(0.0),
+        _ => match arg.strip_suffix('%') {
+            Some(value) => value.parse::<f32>().ok().map(|v| v / 100.0 * percent),
+            None => arg.parse().ok(),
+        },
+    }
+}
+
+fn alpha(arg: Option<&&str>) -> Option<f32> {
+    match arg {
+        Some(arg) => Some(number(arg, 1.0)?.clamp(0.0, 1.0)),
+        None => Some(1.0),
+    }
+}
+
+fn parse_rgb(args: &[&str]) -> Option<Color> {
+    if args.len() != 3 && args.len() != 4 {
+        return None;
+    }
+    let channel = |arg: &str| Some(number(arg, 255.0)?.round().clamp(0.0, 255.0) as u8);
+    Some(Color::new(
+        channel(args[0])?,
+        channel(args[1])?,
+        channel(args[2])?,
+        alpha(args.get(3))?,
+    ))
+}
+
+fn parse_hsl(args: &[&str]) -> Option<Color> {
+    if args.len() != 3 && args.len() != 4 {
+        return None;
+    }
+    let hue = hue(args[0])?;
+    // Saturation and lightness are percentages, though the `%` is optional in
+    // the space separated syntax
+    let fraction =
+        |arg: &str| Some((number(arg.trim_end_matches('%'), 1.0)? / 100.0).clamp(0.0, 1.0));
+    let hsl: Hsl = Hsl::new(hue, fraction(args[1])?, fraction(args[2])?);
+    let rgb = Srgb::from_color(hsl).into_format::<u8>();
+    Some(Color::new(
+        rgb.red,
+        rgb.green,
+        rgb.blue,
+        alpha(args.get(3))?,
+    ))
+}
+
+/// A hue in degrees, from an angle in any unit (degrees if not given)
+fn hue(arg: &str) -> Option<f32> {
+    let (value, degrees) = if let Some(value) = arg.strip_suffix("deg") {
+        (value, 1.0)
+    } else if let Some(value) = arg.strip_suffix("grad") {
+        (value, 0.9)
+    } else if let Some(value) = arg.strip_suffix("rad") {
+        (value, 180.0 / std::f32::consts::PI)
+    } else if let Some(value) = arg.strip_suffix("turn") {
+        (value, 360.0)
+    } else {
+        (arg, 1.0)
+    };
+    Some(number(value, 1.0)? * degrees)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::properties::Value;
+
+    fn rgba(red: u8, green: u8, blue: u8, alpha: f32) -> Result<Color, ColorParseError> {
+        Ok(Color::new(red, green, blue, alpha))
+    }
+
+    #[test]
+    fn parses_hex_colors() {
+        assert_eq!(parse_color("#f0a"), rgba(255, 0, 170, 1.0));
+        assert_eq!(parse_color("#F0A8"), rgba(255, 0, 170, 136.0 / 255.0));
+        assert_eq!(parse_color("#12ab3c"), rgba(18, 171, 60, 1.0));
+        assert_eq!(parse_color(" #12ab3c00 "), rgba(18, 171, 60, 0.0));
+    }
+
+    #[test]
+    fn parses_rgb_colors() {
+        assert_eq!(parse_color("rgb(255, 128, 0)"), rgba(255, 128, 0, 1.0));
+        assert_eq!(parse_color("rgba(255,128,0,0.5)"), rgba(255, 128, 0, 0.5));
+        assert_eq!(
+            parse_color("rgb(100% 50% 0% / 25%)"),
+            rgba(255, 128, 0, 0.25)
+        );
+        // Out of range channels are clamped
+        assert_eq!(parse_color("rgb(300, -20, 0, 2)"), rgba(255, 0, 0, 1.0));
+    }
+
+    #[test]
+    fn parses_hsl_colors() {
+        assert_eq!(parse_color("hsl(0, 100%, 50%)"), rgba(255, 0, 0, 1.0));
+        assert_eq!(
+            parse_color("hsla(120, 100%, 25%, 0.5)"),
+            rgba(0, 128, 0, 0.5)
+        );
+        assert_eq!(parse_color("hsl(0.5turn 100% 50%)"), rgba(0, 255, 255, 1.0));
+        assert_eq!(parse_color("hsl(240deg 100 50 / 1)"), rgba(0, 0, 255, 1.0));
+        assert_eq!(parse_color("hsl(0, 0%, 100%)"), rgba(255, 255, 255, 1.0));
+    }
+
+    #[test]
+    fn parses_named_colors() {
+        assert_eq!(parse_color("red"), rgba(255, 0, 0, 1.0));
+        assert_eq!(parse_color("RebeccaPurple"), rgba(102, 51, 153, 1.0));
+        assert_eq!(parse_color("transparent"), rgba(0, 0, 0, 0.0));
+    }
+
+    #[test]
+    fn rejects_invalid_colors() {
+        for text in [
+            "",
+            "#",
+            "#12",
+            "#12345",
+            "#ggg",
+            "rgb(1, 2)",
+            "rgb(1, 2, 3",
+            "rgb(a, b, c)",
+            "hsl(1, 2, 3, 4, 5)",
+            "cmyk(0, 0, 0, 0)",
+            "notacolor",
+        ] {
+            assert_eq!(parse_color(text), Err(ColorParseError(text.to_string())));
+        }
+    }
+
+    #[test]
+    fn formats_colors() {
+        let color = parse_color("#ff8000").unwrap();
+        assert_eq!(Value::from(color).to_string(), "rgba(255, 128, 0, 1)");
+        assert_eq!(
+            "rgba(255, 128, 0, 0.5)".parse(),
+            Ok(Value::from(rgba(255, 128, 0, 0.5).unwrap()))
+        );
+    }
+}
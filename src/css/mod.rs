@@ -1 +1,5 @@
+//! Parsing values as they're written in CSS
 
+mod color;
+
+pub use color::{parse_color, ColorParseError};
@@ -71,7 +71,7 @@ pub fn spring(mass: f32, stiffness: f32, damping: f32, initial_velocity: f32) ->
         damping,
         initial_velocity,
     })
-    .map(|s| Easing::Spring(s))
+    .map(Easing::Spring)
 }
 
 /// Quad Easing In
@@ -2,8 +2,6 @@ mod bezier;
 pub mod helpers;
 mod spring;
 
-pub use spring::SpringProps;
-
 #[derive(Debug, Clone, PartialEq)]
 pub enum Easing {
     Linear,
@@ -43,6 +41,7 @@ pub enum Direction {
 
 impl Direction {
     fn calculate<F: Fn(f32) -> f32>(&self, f: F, t: f32) -> f32 {
+        use Direction::*;
         match self {
             In => f(t),
             Out => 1.0 - f(1.0 - t),
@@ -57,7 +56,7 @@ impl Direction {
 }
 
 impl Easing {
-    fn at(&self, fraction: f32) -> f32 {
+    pub(crate) fn at(&self, fraction: f32) -> f32 {
         use Direction::*;
         use Easing::*;
         let t = fraction.clamp(0.0, 1.0);
@@ -153,15 +152,20 @@ impl Easing {
     }
 }
 
+// Not used until there's a `Back` easing
+#[allow(dead_code)]
 const BACK_C1: f32 = 1.70158;
+#[allow(dead_code)]
 const BACK_C3: f32 = BACK_C1 + 1.0;
 
+#[allow(dead_code)]
 fn back_in(t: f32) -> f32 {
     let t2 = t * t;
     let t3 = t2 * t;
     (BACK_C3 * t3) - (BACK_C1 * t2)
 }
 
+#[allow(dead_code)]
 fn back_out(t: f32) -> f32 {
     let t1 = t - 1.0;
     let t2 = t1 * t1;
@@ -0,0 +1,180 @@
+//! Frame sources for driving the engine
+//!
+//! By default, the engine is driven by the browser's `requestAnimationFrame`
+//! loop (see [`AnimationFrameClock`]). Outside of a browser (or when
+//! deterministic timing is needed), a [`ManualClock`] can be used instead, and
+//! frames are produced by calling [`Engine::advance`](super::Engine::advance).
+
+use std::fmt::Debug;
+
+use super::EngineError;
+
+/// Called by a [`Clock`] with the current time (in milliseconds) when a
+/// requested frame is ready
+pub type FrameCallback = Box<dyn FnOnce(f32)>;
+
+/// A source of animation frames for the engine
+pub trait Clock: Debug {
+    /// Schedule `callback` to be called on the next frame, returning an
+    /// identifier that can be passed to [`Clock::cancel_frame`]
+    fn request_frame(&mut self, callback: FrameCallback) -> Result<i32, EngineError>;
+
+    /// Cancel a frame previously scheduled with [`Clock::request_frame`]
+    fn cancel_frame(&mut self, frame: i32);
+
+    /// Whether the clock is tied to the document's visibility (and the engine
+    /// should listen for "visibilitychange" events)
+    fn tracks_visibility(&self) -> bool {
+        false
+    }
+
+    /// Whether the document is currently hidden. Only meaningful if
+    /// [`Clock::tracks_visibility`] is true
+    fn document_hidden(&self) -> bool {
+        false
+    }
+
+    /// Move the clock forward by `elapsed` milliseconds, returning the pending
+    /// frame callback (if any) along with the new time.
+    ///
+    /// Clocks that are driven by the host (like the browser) ignore this
+    fn advance(&mut self, _elapsed: f32) -> Option<(FrameCallback, f32)> {
+        None
+    }
+}
+
+/// The default clock, driven by the browser's `requestAnimationFrame`
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AnimationFrameClock;
+
+impl Clock for AnimationFrameClock {
+    fn request_frame(&mut self, callback: FrameCallback) -> Result<i32, EngineError> {
+        crate::util::request_animation_frame(move |t| callback(t as f32))
+            .map_err(|_| EngineError::RafFailure)
+    }
+
+    fn cancel_frame(&mut self, frame: i32) {
+        let _ = crate::util::window().cancel_animation_frame(frame);
+    }
+
+    fn tracks_visibility(&self) -> bool {
+        true
+    }
+
+    fn document_hidden(&self) -> bool {
+        crate::util::document_is_hidden()
+    }
+}
+
+/// A clock that only produces frames when told to, through
+/// [`Engine::advance`](super::Engine::advance)
+///
+/// Time starts at zero and only moves forward by the amounts passed to
+/// `advance`, so animations can be stepped deterministically (e.g. in native
+/// tests)
+#[derive(Default)]
+pub struct ManualClock {
+    now: f32,
+    next_frame: i32,
+    pending: Option<(i32, FrameCallback)>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The current time of this clock, in milliseconds
+    pub fn now(&self) -> f32 {
+        self.now
+    }
+}
+
+impl Debug for ManualClock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ManualClock")
+            .field("now", &self.now)
+            .field("pending", &self.pending.as_ref().map(|(frame, _)| frame))
+            .finish()
+    }
+}
+
+impl Clock for ManualClock {
+    fn request_frame(&mut self, callback: FrameCallback) -> Result<i32, EngineError> {
+        self.next_frame += 1;
+        self.pending = Some((self.next_frame, callback));
+        Ok(self.next_frame)
+    }
+
+    fn cancel_frame(&mut self, frame: i32) {
+        if matches!(self.pending, Some((pending, _)) if pending == frame) {
+            self.pending = None;
+        }
+    }
+
+    fn advance(&mut self, elapsed: f32) -> Option<(FrameCallback, f32)> {
+        self.now += elapsed;
+        let now = self.now;
+        self.pending.take().map(|(_, callback)| (callback, now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use super::*;
+    use crate::engine::EngineBuilder;
+
+    /// A frame callback recording the time it was called with
+    fn record(called: &Rc<Cell<Option<f32>>>) -> FrameCallback {
+        let called = called.clone();
+        Box::new(move |t| called.set(Some(t)))
+    }
+
+    #[test]
+    fn runs_pending_frames_when_advanced() {
+        let called = Rc::new(Cell::new(None));
+        let mut clock = ManualClock::new();
+        assert!(clock.advance(10.0).is_none());
+
+        clock.request_frame(record(&called)).unwrap();
+        let (callback, now) = clock.advance(15.0).unwrap();
+        callback(now);
+        assert_eq!(called.get(), Some(25.0));
+        assert_eq!(clock.now(), 25.0);
+
+        // Frames only run once
+        assert!(clock.advance(10.0).is_none());
+    }
+
+    #[test]
+    fn cancels_frames() {
+        let called = Rc::new(Cell::new(None));
+        let mut clock = ManualClock::new();
+        let frame = clock.request_frame(record(&called)).unwrap();
+
+        // Stale frames are ignored
+        clock.cancel_frame(frame - 1);
+        clock.cancel_frame(frame);
+        assert!(clock.advance(10.0).is_none());
+        assert_eq!(called.get(), None);
+    }
+
+    #[test]
+    fn drives_the_engine() {
+        let engine = EngineBuilder::manual()
+            .init()
+            .expect("Failed to initialize engine");
+        assert!(engine.running());
+
+        // Without anything to play, the engine stops until it's run again
+        engine.advance(16.0).unwrap();
+        assert!(!engine.running());
+        engine.advance(16.0).unwrap();
+        assert!(!engine.running());
+
+        engine.run().unwrap();
+        assert!(engine.running());
+    }
+}
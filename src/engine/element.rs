@@ -0,0 +1,115 @@
+//! The DOM operations animations perform on their targets
+//!
+//! Targets are usually [`HtmlElement`]s, but animations only use them through
+//! the [`Element`] trait, so they can be stepped outside of a browser (e.g. in
+//! native tests, with a `StyleMap`).
+
+use std::fmt::Debug;
+
+use wasm_bindgen::JsCast;
+use web_sys::HtmlElement;
+
+use crate::properties::Unit;
+
+/// An animation target
+pub(crate) trait Element: Debug {
+    /// The computed value of a css property (by its kebab-case name), if set
+    fn computed_style(&self, name: &str) -> Option<String>;
+
+    /// The inline value of a css property, if set
+    fn inline_style(&self, name: &str) -> Option<String>;
+
+    /// Set the inline value of a css property
+    fn set_style(&self, name: &str, value: &str);
+
+    /// The size of a relative length unit (%, em, vw, ...) in px, in this
+    /// element's context
+    fn measure(&self, unit: Unit) -> Option<f32>;
+}
+
+impl Element for HtmlElement {
+    fn computed_style(&self, name: &str) -> Option<String> {
+        crate::util::window()
+            .get_computed_style(self)
+            .ok()
+            .flatten()
+            .and_then(|style| style.get_property_value(name).ok())
+            .filter(|value| !value.is_empty())
+    }
+
+    fn inline_style(&self, name: &str) -> Option<String> {
+        self.style()
+            .get_property_value(name)
+            .ok()
+            .filter(|value| !value.is_empty())
+    }
+
+    fn set_style(&self, name: &str, value: &str) {
+        let _ = self.style().set_property(name, value);
+    }
+
+    /// Measured with a temporary element (of the same type) in this element's
+    /// parent
+    fn measure(&self, unit: Unit) -> Option<f32> {
+        const BASELINE: f32 = 100.0;
+
+        let document = crate::util::document();
+        let temp: HtmlElement = document
+            .create_element(&self.tag_name())
+            .ok()?
+            .dyn_into()
+            .ok()?;
+        let parent: web_sys::Node = match self.parent_element() {
+            Some(parent) => parent.into(),
+            None => document.body()?.into(),
+        };
+        parent.append_child(&temp).ok()?;
+
+        let style = temp.style();
+        let _ = style.set_property("position", "absolute");
+        let _ = style.set_property("width", &format!("{}{}", BASELINE, unit.as_str()));
+        let width = temp.offset_width() as f32;
+        let _ = parent.remove_child(&temp);
+
+        if width > 0.0 {
+            Some(width / BASELINE)
+        } else {
+            None
+        }
+    }
+}
+
+/// An element that's only a map of styles (with no computed styles other than
+/// its inline ones, and no relative units), for native tests
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub(crate) struct StyleMap(std::cell::RefCell<std::collections::HashMap<String, String>>);
+
+#[cfg(test)]
+impl StyleMap {
+    /// The value of a style, if set
+    pub fn get(&self, name: &str) -> Option<String> {
+        self.0.borrow().get(name).cloned()
+    }
+}
+
+#[cfg(test)]
+impl Element for StyleMap {
+    fn computed_style(&self, name: &str) -> Option<String> {
+        self.get(name)
+    }
+
+    fn inline_style(&self, name: &str) -> Option<String> {
+        self.get(name)
+    }
+
+    fn set_style(&self, name: &str, value: &str) {
+        self.0
+            .borrow_mut()
+            .insert(name.to_string(), value.to_string());
+    }
+
+    fn measure(&self, _unit: Unit) -> Option<f32> {
+        None
+    }
+}
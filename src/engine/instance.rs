@@ -1,22 +1,20 @@
-use std::{
-    fmt::Debug,
-    sync::{Arc, RwLock},
-};
+use std::{cell::RefCell, collections::HashMap, fmt::Debug, rc::Rc};
 
+use super::element::Element;
 use crate::{
+    builder::{
+        property::{tweens, TweenParameters},
+        AnimationCallback,
+    },
     easings::Easing,
     engine::Speed,
-    Repeat,
-    properties::{Property, Value},
-    target::Target,
-    Direction, Engine,
+    properties::{Interpolation, PropertyValue, Tweenable, Unit, Value},
+    Direction, Repeat,
 };
 
 #[derive(Debug)]
 pub struct Instance {
     // id: u32, // TODO: not necessary?
-    engine: Engine, // hold reference to parent engine
-
     /// Set to true to disable event callbacks (mainly for timelines)
     passthrough: bool, // TODO: not necessary?
 
@@ -30,17 +28,32 @@ pub struct Instance {
     change_completed: bool,
 
     repeat: Repeat,
+    /// Iterations left to play (`None` if repeating forever)
+    remaining: Option<u32>,
 
-    start_time: f32,
+    /// Engine speed, as of the last tick
+    engine_speed: Speed,
+    /// Playback speed of this instance (relative to its parent timeline, or
+    /// the engine)
+    speed: Speed,
+
+    /// Engine time of the first tick since the last time reset
+    start_time: Option<f32>,
     current_time: f32,
     last_time: f32,
     now: f32,
-    progress: u32,
+    /// Percent complete (`0.0..=100.0`)
+    progress: f32,
 
     children: Vec<Instance>,
     animations: Vec<Animation>,
 
-    // Timings // TODO: getInstanceTimings
+    /// Start time within the parent timeline (if a timeline child)
+    timeline_offset: f32,
+    /// Named positions on this timeline
+    labels: Vec<(String, f32)>,
+
+    // Timings
     duration: f32,
     delay: f32,
     end_delay: f32,
@@ -49,59 +62,606 @@ pub struct Instance {
     direction: Direction,
     reversed: bool,
     reverse_playback: bool,
+
+    /// Whether the animations have been resolved against their targets'
+    /// values, since the last reset or the start of playback (so relative
+    /// values are relative to the targets' values when playback starts)
+    resolved: bool,
+
+    callbacks: Callbacks,
+
+    /// Number of times this instance has completed (to resolve futures)
+    #[cfg(feature = "animation-futures")]
+    completions: u32,
+    /// Wakers of futures waiting for this instance to complete (or be removed)
+    #[cfg(feature = "animation-futures")]
+    wakers: Vec<std::task::Waker>,
+}
+
+/// Event callbacks for an [`Instance`]
+#[derive(Default)]
+pub(crate) struct Callbacks {
+    pub update: Option<Box<dyn AnimationCallback>>,
+
+    pub begin: Option<Box<dyn AnimationCallback>>,
+    pub complete: Option<Box<dyn AnimationCallback>>,
+
+    pub loop_begin: Option<Box<dyn AnimationCallback>>,
+    pub loop_complete: Option<Box<dyn AnimationCallback>>,
+
+    pub change: Option<Box<dyn AnimationCallback>>,
+    pub change_begin: Option<Box<dyn AnimationCallback>>,
+    pub change_complete: Option<Box<dyn AnimationCallback>>,
+}
+
+impl Debug for Callbacks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let set = |cb: &Option<Box<dyn AnimationCallback>>| cb.is_some();
+        f.debug_struct("Callbacks")
+            .field("update", &set(&self.update))
+            .field("begin", &set(&self.begin))
+            .field("complete", &set(&self.complete))
+            .field("loop_begin", &set(&self.loop_begin))
+            .field("loop_complete", &set(&self.loop_complete))
+            .field("change", &set(&self.change))
+            .field("change_begin", &set(&self.change_begin))
+            .field("change_complete", &set(&self.change_complete))
+            .finish()
+    }
 }
 
 // For a single target
-struct Animatable {
-    // From targets
-    target: Target,
-    total: u32, // length of parent?
-    transforms: (), // {
-                // list: Vec<getElementTransforms>
-                // }
+#[derive(Debug)]
+pub(crate) struct Animatable {
+    target: Rc<dyn Element>,
+    /// Values read from the target before it was animated (see
+    /// [`Animatable::original`])
+    original: RefCell<HashMap<AnimationKind, Option<String>>>,
+    /// Measured sizes of relative units in px (see [`Animatable::unit_factor`])
+    unit_sizes: RefCell<HashMap<Unit, Option<f32>>>,
+    /// Transform functions (name and arguments) set since they were last
+    /// written to the target (see [`Animatable::set_transform`])
+    transforms: RefCell<Vec<(String, String)>>,
+}
+
+impl Animatable {
+    pub fn new(target: Rc<dyn Element>) -> Self {
+        Self {
+            target,
+            original: RefCell::new(HashMap::new()),
+            unit_sizes: RefCell::new(HashMap::new()),
+            transforms: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Read the target's current value for an animation, as css text
+    pub fn read(&self, kind: &AnimationKind) -> Option<String> {
+        match kind {
+            AnimationKind::Css(name) => self.target.computed_style(name),
+            AnimationKind::Transform(name) => self
+                .transform_list()
+                .into_iter()
+                .find(|(function, _)| function.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value),
+        }
+    }
+
+    /// Set the arguments of one of the target's transform functions, to be
+    /// written with the rest by [`Animatable::write_transforms`]
+    pub fn set_transform(&self, name: &str, value: String) {
+        let mut transforms = self.transforms.borrow_mut();
+        match transforms
+            .iter_mut()
+            .find(|(function, _)| function.eq_ignore_ascii_case(name))
+        {
+            Some((_, current)) => *current = value,
+            None => transforms.push((name.to_string(), value)),
+        }
+    }
+
+    /// Merge the transform functions set since the last write into the
+    /// target's inline `transform`, and write it as a single string.
+    ///
+    /// The inline style is the registry of an element's transforms, so those
+    /// set by something else (or another animation of the same element) are
+    /// kept. Functions keep their place, and any the element didn't have go
+    /// after the rest, in the order they were first set
+    pub fn write_transforms(&self) {
+        let mut transforms = self.transforms.borrow_mut();
+        if transforms.is_empty() {
+            return;
+        }
+
+        let mut list = self.transform_list();
+        for (name, value) in transforms.drain(..) {
+            // Function names are case-insensitive (e.g. `matrix3D`, which
+            // browsers write as `matrix3d`)
+            let existing = list
+                .iter_mut()
+                .find(|(function, _)| function.eq_ignore_ascii_case(&name));
+            match existing {
+                Some((_, current)) => *current = value,
+                None => list.push((name, value)),
+            }
+        }
+
+        let transform = list
+            .iter()
+            .map(|(name, value)| format!("{}({})", name, value))
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.target.set_style("transform", &transform);
+    }
+
+    /// The transform functions (name and arguments) in the target's inline
+    /// `transform`, in order
+    fn transform_list(&self) -> Vec<(String, String)> {
+        crate::util::parse_transforms(&self.target.inline_style("transform").unwrap_or_default())
+    }
+
+    /// Set a css property on the target
+    pub fn set_style(&self, name: &str, value: &str) {
+        self.target.set_style(name, value);
+    }
+
+    /// The target's value for an animation before it was animated. Read the
+    /// first time it's needed, then cached so restarts start from the same
+    /// place
+    pub fn original(&self, kind: &AnimationKind) -> Option<String> {
+        self.original
+            .borrow_mut()
+            .entry(kind.clone())
+            .or_insert_with(|| self.read(kind))
+            .clone()
+    }
+
+    /// What to multiply a value in `from` units by to convert it to `to` units
+    /// for this target. Relative units (%, em, vw, ...) are measured in the
+    /// target's parent, the rest are converted with [`Unit::factor`]
+    pub fn unit_factor(&self, from: Unit, to: Unit) -> Option<f32> {
+        Unit::factor(from, to).or_else(|| Some(self.unit_size(from)? / self.unit_size(to)?))
+    }
+
+    /// Size of a length unit in px (measured once, then cached)
+    fn unit_size(&self, unit: Unit) -> Option<f32> {
+        if let Some(px) = unit.absolute_px() {
+            return Some(px);
+        }
+        *self.unit_sizes.borrow_mut().entry(unit).or_insert_with(|| {
+            // Only lengths can be measured
+            if matches!(
+                unit,
+                Unit::Dimensionless | Unit::Deg | Unit::Grad | Unit::Rad | Unit::Turn
+            ) {
+                None
+            } else {
+                self.target.measure(unit)
+            }
+        })
+    }
+}
+
+/// How an animated value is applied to its target
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum AnimationKind {
+    /// A css property, by its css (kebab-case) name
+    Css(String),
+    /// A css transform function, by its name
+    Transform(&'static str),
 }
 
 // For each property on an "animatable"
-#[derive(Debug, PartialEq)]
+#[derive(Debug)]
 pub struct Animation {
-    target: Target,         //?
-    property: Property,     // TODO
-    tweens: Vec<Tween<()>>, // tweens // TODO: value type, based on property?
+    animatable: Option<Rc<Animatable>>,
+    kind: AnimationKind,
+    tweens: Box<dyn Tweens>,
 
     // See getAnimations
-    duration: f32, // Default: 1000
+    duration: f32,
     delay: f32,
     end_delay: f32,
-    easing: Easing,
-    round: u32,
+
+    /// The last value set on the target
+    current_value: Option<String>,
 }
 
 impl Animation {
-    // fn duration() -> Option<f32> {
+    pub(crate) fn new<T: Tweenable>(
+        animatable: Option<Rc<Animatable>>,
+        kind: AnimationKind,
+        tweens: TweenChain<T>,
+    ) -> Self {
+        let duration = tweens.duration();
+        let delay = tweens.delay();
+        let end_delay = tweens.end_delay();
+        Self {
+            animatable,
+            kind,
+            tweens: Box::new(tweens),
+            duration,
+            delay,
+            end_delay,
+            current_value: None,
+        }
+    }
 
-    // }
+    /// The last value set on the target
+    pub fn current_value(&self) -> Option<&str> {
+        self.current_value.as_deref()
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.duration
+    }
+
+    pub fn delay(&self) -> f32 {
+        self.delay
+    }
+
+    pub fn end_delay(&self) -> f32 {
+        self.end_delay
+    }
+
+    /// Resolve the tweens against the target's original and current values
+    fn resolve(&mut self) {
+        match &self.animatable {
+            Some(animatable) => {
+                let original = animatable.original(&self.kind);
+                let current = animatable.read(&self.kind);
+                self.tweens
+                    .resolve(original.as_deref(), current.as_deref(), &|from, to| {
+                        animatable.unit_factor(from, to)
+                    });
+            }
+            None => self.tweens.resolve(None, None, &Unit::factor),
+        }
+    }
+
+    /// Update the value on the target. Transforms are only collected, to be
+    /// written together by [`Animation::write_transforms`]
+    fn set_progress(&mut self, instance_time: f32) {
+        let value = self.tweens.value_at(instance_time);
+        if let Some(animatable) = &self.animatable {
+            match &self.kind {
+                AnimationKind::Css(name) => animatable.set_style(name, &value),
+                AnimationKind::Transform(name) => animatable.set_transform(name, value.clone()),
+            }
+        }
+        self.current_value = Some(value);
+    }
+
+    /// Write the target's transforms, if this (or another animation of the
+    /// same target) has changed them
+    fn write_transforms(&self) {
+        if let Some(animatable) = &self.animatable {
+            animatable.write_transforms();
+        }
+    }
 }
 
+/// A single transition of a value from `from` to `to`
 #[derive(Debug, PartialEq)]
-struct Tween<T: Debug> {
-    from: Value<T>,
-    to: Value<T>,
+pub(crate) struct Tween<T: Debug> {
+    pub from: Value<T>,
+    pub to: Value<T>,
+
+    /// Start time, relative to the start of the animation
+    pub start: f32,
+    /// End time (including delays), relative to the start of the animation
+    pub end: f32,
+
+    pub duration: f32,
+    pub delay: f32,
+    pub end_delay: f32,
+
+    pub easing: Easing,
+    pub round: u32,
+    pub interpolation: Interpolation,
+    // is_path, is_path_target_inside_svg
+}
+
+impl<T: Tweenable> Tween<T> {
+    /// Eased progress through this tween at the given animation time
+    fn eased(&self, time: f32) -> f32 {
+        if self.duration <= 0.0 {
+            return 1.0;
+        }
+        let elapsed = (time - self.start - self.delay).clamp(0.0, self.duration) / self.duration;
+        self.easing.at(elapsed)
+    }
+
+    fn value_at(&self, time: f32) -> Value<T> {
+        let mut value = T::interpolate_with(
+            self.from.value(),
+            self.to.value(),
+            self.eased(time),
+            &self.interpolation,
+        );
+        if self.round > 0 {
+            value = value.round(self.round);
+        }
+        Value::new(value, self.to.unit().or_else(|| self.from.unit()))
+    }
+}
+
+/// A chain of [`Tween`]s for a single property, erasing the value type
+pub(crate) trait Tweens: Debug {
+    /// Rebuild the tweens, starting from the target's `original` value (or
+    /// its `current` value, for relative values), as css text, if they could
+    /// be read. `factor` converts between units for the target
+    fn resolve(
+        &mut self,
+        original: Option<&str>,
+        current: Option<&str>,
+        factor: &dyn Fn(Unit, Unit) -> Option<f32>,
+    );
+
+    /// End of the last tween
+    fn duration(&self) -> f32;
+    /// Delay of the first tween
+    fn delay(&self) -> f32;
+    /// End delay of the last tween
+    fn end_delay(&self) -> f32;
+
+    /// The css value at the given animation time
+    fn value_at(&self, time: f32) -> String;
+}
+
+/// The tweens for a property, along with the value they were built from, so
+/// they can be rebuilt when the instance starts
+#[derive(Debug)]
+pub(crate) struct TweenChain<T: Tweenable> {
+    value: PropertyValue<T>,
+    params: TweenParameters,
+    /// Value to start from, if the target's value can't be read
+    initial: Value<T>,
+    default_unit: Option<Unit>,
+
+    tweens: Vec<Tween<T>>,
+}
+
+impl<T: Tweenable> TweenChain<T> {
+    pub fn new(
+        value: PropertyValue<T>,
+        params: TweenParameters,
+        initial: Value<T>,
+        default_unit: Option<Unit>,
+    ) -> Self {
+        let tweens = tweens(
+            &value,
+            &params,
+            initial.clone(),
+            default_unit,
+            &Unit::factor,
+        );
+        Self {
+            value,
+            params,
+            initial,
+            default_unit,
+            tweens,
+        }
+    }
+}
+
+impl<T: Tweenable> Tweens for TweenChain<T> {
+    fn resolve(
+        &mut self,
+        original: Option<&str>,
+        current: Option<&str>,
+        factor: &dyn Fn(Unit, Unit) -> Option<f32>,
+    ) {
+        // Relative values (`+=`, `-=`, `*=`) are relative to the current value
+        let read = match &self.value {
+            PropertyValue::Relative(_) => current,
+            _ => original,
+        };
+        let initial = read
+            .and_then(T::parse_css)
+            .unwrap_or_else(|| self.initial.clone());
+        self.tweens = tweens(
+            &self.value,
+            &self.params,
+            initial,
+            self.default_unit,
+            factor,
+        );
+    }
+
+    fn duration(&self) -> f32 {
+        self.tweens
+            .last()
+            .map(|tween| tween.end)
+            .unwrap_or_default()
+    }
+
+    fn delay(&self) -> f32 {
+        self.tweens
+            .first()
+            .map(|tween| tween.delay)
+            .unwrap_or_default()
+    }
+
+    fn end_delay(&self) -> f32 {
+        self.tweens
+            .last()
+            .map(|tween| tween.end_delay)
+            .unwrap_or_default()
+    }
 
-    start: f32,
-    end: f32,
-    easing: Easing,
-    // is_path, is_path_target_inside_svg, is_color
+    fn value_at(&self, time: f32) -> String {
+        // The first tween that hasn't ended, or the last one
+        self.tweens
+            .iter()
+            .find(|tween| time < tween.end)
+            .or_else(|| self.tweens.last())
+            .map(|tween| tween.value_at(time).to_string())
+            .unwrap_or_default()
+    }
 }
 
 impl Instance {
+    /// Create a new (paused) instance. `defaults` provide the timings if there
+    /// are no animations
+    pub(crate) fn new(
+        animations: Vec<Animation>,
+        direction: Direction,
+        repeat: Repeat,
+        autoplay: bool,
+        callbacks: Callbacks,
+        defaults: &TweenParameters,
+    ) -> Self {
+        let mut instance = Self {
+            passthrough: false,
+            autoplay,
+
+            began: false,
+            loop_began: false,
+            change_began: false,
+            paused: true,
+            completed: false,
+            change_completed: false,
+
+            repeat,
+            remaining: None,
+
+            engine_speed: Speed::Normal,
+            speed: Speed::Normal,
+
+            start_time: None,
+            current_time: 0.0,
+            last_time: 0.0,
+            now: 0.0,
+            progress: 0.0,
+
+            children: Vec::new(),
+            animations,
+
+            timeline_offset: 0.0,
+            labels: Vec::new(),
+
+            duration: defaults.duration,
+            delay: defaults.delay,
+            end_delay: defaults.end_delay,
+
+            direction,
+            reversed: false,
+            reverse_playback: false,
+            resolved: false,
+
+            callbacks,
+
+            #[cfg(feature = "animation-futures")]
+            completions: 0,
+            #[cfg(feature = "animation-futures")]
+            wakers: Vec::new(),
+        };
+        instance.set_timings();
+        instance.reset();
+        instance
+    }
+
+    /// Called when added to an engine, to start playback if autoplaying
+    pub(crate) fn attach(&mut self, speed: Speed) {
+        self.engine_speed = speed;
+        if self.autoplay {
+            self.play();
+        }
+    }
+
     pub fn active(&self) -> bool {
-        true // TODO
+        !self.paused
+    }
+
+    pub fn tick(&mut self, time: f32, speed: Speed) {
+        self.engine_speed = speed;
+        self.now = time;
+        let start_time = *self.start_time.get_or_insert(time);
+        self.resolve_animations();
+        self.set_instance_progress((time + (self.last_time - start_time)) * self.time_scale());
+    }
+
+    /// Create an empty (paused) timeline, with children added by
+    /// [`Instance::add`]
+    pub(crate) fn timeline(direction: Direction, repeat: Repeat, autoplay: bool) -> Self {
+        Self::new(
+            Vec::new(),
+            direction,
+            repeat,
+            autoplay,
+            Callbacks::default(),
+            &TweenParameters {
+                duration: 0.0,
+                delay: 0.0,
+                end_delay: 0.0,
+                ..TweenParameters::default()
+            },
+        )
     }
 
-    pub fn tick(&mut self, time: i32) {}
+    /// Add a child to this timeline, starting at `offset` milliseconds.
+    ///
+    /// The child keeps its own speed and direction, which are relative to
+    /// this timeline's
+    pub fn add(&mut self, mut child: Instance, offset: f32) {
+        child.timeline_offset = offset;
+        child.autoplay = false;
+        child.reset();
+        child.inherit_reversed(self.reversed);
+        self.children.push(child);
 
-    pub fn add(&mut self, child: Instance) {
-        todo!() // TODO: timeline specific
+        self.set_timings();
+        self.seek(0.0);
+        self.reset();
+    }
+
+    /// Seek to `time` without firing callbacks (or resolving futures), to put
+    /// the targets in the state they'd be in at that time. Callbacks fire
+    /// again after the next reset
+    pub(crate) fn seek_passthrough(&mut self, time: f32) {
+        self.set_passthrough();
+        self.seek(time);
+    }
+
+    fn set_passthrough(&mut self) {
+        self.passthrough = true;
+        self.children.iter_mut().for_each(Self::set_passthrough);
+    }
+
+    pub(crate) fn set_labels(&mut self, labels: Vec<(String, f32)>) {
+        self.labels = labels;
+    }
+
+    /// Position of a timeline label
+    pub fn label(&self, name: &str) -> Option<f32> {
+        self.labels
+            .iter()
+            .find(|(label, _)| label == name)
+            .map(|(_, position)| *position)
+    }
+
+    /// Start time within the parent timeline
+    pub fn timeline_offset(&self) -> f32 {
+        self.timeline_offset
+    }
+
+    /// End time (including delays) within the parent timeline
+    pub fn timeline_end(&self) -> f32 {
+        self.timeline_offset + (self.duration / self.speed.multiplier())
+    }
+
+    /// Playback speed, relative to the parent timeline (or engine)
+    pub fn speed(&self) -> Speed {
+        self.speed
+    }
+
+    pub(crate) fn set_speed(&mut self, speed: Speed) {
+        self.speed = speed;
+    }
+
+    pub fn children(&self) -> &[Instance] {
+        &self.children
     }
 
     /*
@@ -116,11 +676,17 @@ impl Instance {
         if self.completed {
             self.reset()
         }
+        if !self.began {
+            // Starting from the beginning, so resolve against the targets'
+            // values as of the first tick (or, for children, their first
+            // update)
+            self.unresolve();
+        }
         self.paused = false;
-
-        // TODO: add to active instances?
         self.reset_time();
-        let _ = self.engine.run(); // TODO: error
+
+        // NOTE: the engine needs to be (re)started by the caller, since we
+        // don't hold a reference to it here
     }
 
     pub fn pause(&mut self) {
@@ -129,9 +695,7 @@ impl Instance {
     }
 
     pub fn reverse(&mut self) {
-        self.direction.reverse();
-        self.reversed = !self.reversed;
-        // TODO: reverse all children
+        self.toggle_direction();
         self.completed = !self.reversed;
         self.reset_time();
     }
@@ -141,12 +705,17 @@ impl Instance {
         self.play();
     }
 
+    /// Jump to `time` milliseconds into the current iteration
+    pub fn seek(&mut self, time: f32) {
+        self.set_instance_progress(self.adjust_time(time));
+    }
+
     // TODO: remove
 
     pub fn reset(&mut self) {
         self.passthrough = false;
         self.current_time = 0f32;
-        self.progress = 0;
+        self.progress = 0.0;
         self.paused = true;
         self.began = false;
         self.loop_began = false;
@@ -154,40 +723,73 @@ impl Instance {
         self.completed = false;
         self.change_completed = false;
         self.reverse_playback = false;
+        self.resolved = false;
         self.reversed = matches!(self.direction, Direction::Reverse);
 
-        // self.remaining = instance.loop;
+        self.remaining = self.repeat.remaining();
 
-        self.children.iter_mut().for_each(Self::reset);
+        // Last to first, so where children animate the same property, the
+        // first one's starting value is the one left on the target
+        self.children.iter_mut().rev().for_each(Self::reset);
+        let reversed = self.reversed;
+        self.children
+            .iter_mut()
+            .for_each(|child| child.inherit_reversed(reversed));
 
         if (self.reversed && matches!(self.repeat, Repeat::Finite { .. }))
             || (matches!(self.direction, Direction::Alternate)
                 && matches!(self.repeat, Repeat::Finite { total: 1, .. }))
         {
-            self.repeat.add(1)
+            if let Some(remaining) = &mut self.remaining {
+                *remaining += 1;
+            }
         }
 
-        // TODO: setAnimationsProgress
         self.set_animations_progress(if self.reversed { self.duration } else { 0.0 });
     }
-    // TODO: pub?
-    fn reset_time(&mut self) {
-        self.start_time = 0.0;
-        self.last_time = {
-            let mut time = if self.reversed {
-                (self.duration as f32) - self.current_time
-            } else {
-                self.current_time
-            };
-            if let Speed::Multiplied(speed) = self.engine.speed() {
-                time /= speed;
-            }
-            time
-        }
+
+    /// Total duration (including delays) of a single iteration
+    pub fn duration(&self) -> f32 {
+        self.duration
+    }
+
+    /// Time into the current iteration
+    pub fn current_time(&self) -> f32 {
+        self.current_time
+    }
+
+    /// Percent complete of the current iteration (`0.0..=100.0`)
+    pub fn progress(&self) -> f32 {
+        self.progress
+    }
+
+    pub fn began(&self) -> bool {
+        self.began
+    }
+
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn completed(&self) -> bool {
+        self.completed
+    }
+
+    pub fn reversed(&self) -> bool {
+        self.reversed
+    }
+
+    /// Iterations left to play (`None` if repeating forever)
+    pub fn remaining(&self) -> Option<u32> {
+        self.remaining
+    }
+
+    pub fn animations(&self) -> &[Animation] {
+        &self.animations
     }
 
     // TODO: multiple prop/value
-    pub fn set(target: Target, property: &str, value: &str) {} // Helper -> static? or just function?
+    pub fn set(_target: crate::target::Target, _property: &str, _value: &str) {} // Helper -> static? or just function?
 
     /*
 
@@ -195,30 +797,265 @@ impl Instance {
 
     */
 
+    /// Compute the instance timings from its animations and children (see
+    /// `getInstanceTimings`)
+    fn set_timings(&mut self) {
+        // (offset, duration, delay, end delay) of everything we play, in our
+        // time (scaled by the children's speeds)
+        let timings: Vec<(f32, f32, f32, f32)> = self
+            .animations
+            .iter()
+            .map(|a| (0.0, a.duration, a.delay, a.end_delay))
+            .chain(self.children.iter().map(|c| {
+                let scale = c.speed.multiplier();
+                (
+                    c.timeline_offset,
+                    c.duration / scale,
+                    c.delay / scale,
+                    c.end_delay / scale,
+                )
+            }))
+            .collect();
+        if timings.is_empty() {
+            return;
+        }
+
+        self.duration = timings
+            .iter()
+            .map(|(offset, duration, ..)| offset + duration)
+            .fold(f32::MIN, f32::max);
+        self.delay = timings
+            .iter()
+            .map(|(offset, _, delay, _)| offset + delay)
+            .fold(f32::MAX, f32::min);
+        self.end_delay = self.duration
+            - timings
+                .iter()
+                .map(|(offset, duration, _, end_delay)| offset + duration - end_delay)
+                .fold(f32::MIN, f32::max);
+    }
+
+    /// Seek all children to the given (instance) time, in playback order
+    fn sync_children(&mut self, instance_time: f32) {
+        let reversed = self.reversed;
+        let seek = |child: &mut Instance| {
+            // Map to the child's local time
+            let local = (instance_time - child.timeline_offset) * child.speed.multiplier();
+
+            // The child's `reversed` combines ours with its own direction, so
+            // feed it time in our direction of playback (see `inherit_reversed`)
+            child.set_instance_progress(if reversed {
+                child.duration - local
+            } else {
+                local
+            });
+        };
+        if self.reverse_playback {
+            self.children.iter_mut().rev().for_each(seek);
+        } else {
+            self.children.iter_mut().for_each(seek);
+        }
+    }
+
+    /// Restart time tracking from the current position (so the next tick
+    /// continues from here)
+    pub(crate) fn reset_time(&mut self) {
+        self.start_time = None;
+        self.last_time = self.adjust_time(self.current_time) / self.time_scale();
+    }
+
+    /// Ratio of instance time to engine time (for top level instances)
+    fn time_scale(&self) -> f32 {
+        self.engine_speed.multiplier() * self.speed.multiplier()
+    }
+
     pub(crate) fn on_document_visibility(&mut self) {
         self.reset_time()
     }
 
-    fn get_time(&self, engine_time: f32) -> f32 {
-        // TODO
-        todo!()
+    fn toggle_direction(&mut self) {
+        self.direction.reverse();
+        self.reversed = !self.reversed;
+        let reversed = self.reversed;
+        self.children
+            .iter_mut()
+            .for_each(|child| child.inherit_reversed(reversed));
+    }
+
+    /// Combine the parent timeline's playback direction with our own, so a
+    /// reversed parent plays (non-reversed) children backwards, and reversed
+    /// children forwards
+    fn inherit_reversed(&mut self, parent_reversed: bool) {
+        self.reversed = parent_reversed != matches!(self.direction, Direction::Reverse);
+        let reversed = self.reversed;
+        self.children
+            .iter_mut()
+            .for_each(|child| child.inherit_reversed(reversed));
+    }
+
+    /// Map between engine time and instance time, accounting for reversal
+    fn adjust_time(&self, time: f32) -> f32 {
+        if self.reversed {
+            self.duration - time
+        } else {
+            time
+        }
+    }
 
-        // TODO: EngineTime and InstanceTime wrappers?
+    fn count_iteration(&mut self) {
+        if let Some(remaining) = &mut self.remaining {
+            *remaining = remaining.saturating_sub(1);
+        }
+    }
+
+    fn set_callback(&self, callback: &Option<Box<dyn AnimationCallback>>) {
+        if self.passthrough {
+            return;
+        }
+        if let Some(callback) = callback {
+            callback(self)
+        }
     }
 
     // TODO: for perf, only update animation values here,
     // do the dom interaction elsewhere? so we can let go of the write
     // lock as soon as possible
     fn set_animations_progress(&mut self, instance_time: f32) {
+        self.resolve_animations();
         for animation in self.animations.iter_mut() {
-            let fractional_time = todo!();
-            // TODO
-            // animation.update_
+            animation.set_progress(instance_time);
+        }
+        // Once every transform of a target is updated, so each target gets a
+        // single transform string per frame
+        for animation in self.animations.iter() {
+            animation.write_transforms();
         }
     }
 
+    /// Resolve the animations against their targets' values, if they haven't
+    /// been since the last reset (or start of playback). This has to happen
+    /// before anything is written to the targets, or we'd read back our own
+    /// fallback values
+    fn resolve_animations(&mut self) {
+        if !self.resolved {
+            self.resolved = true;
+            self.animations.iter_mut().for_each(Animation::resolve);
+        }
+    }
+
+    /// Resolve the animations (and the children's) again before they're next
+    /// written
+    fn unresolve(&mut self) {
+        self.resolved = false;
+        self.children.iter_mut().for_each(Self::unresolve);
+    }
+
     fn set_instance_progress(&mut self, engine_time: f32) {
-        let instance_time = self.get_time(engine_time);
+        let duration = self.duration;
+        let delay = self.delay;
+        let end_delay = duration - self.end_delay;
+        let instance_time = self.adjust_time(engine_time);
+
+        self.progress = if duration > 0.0 {
+            ((instance_time / duration) * 100.0).clamp(0.0, 100.0)
+        } else {
+            100.0
+        };
+        self.reverse_playback = instance_time < self.current_time;
+
+        if !self.children.is_empty() {
+            self.sync_children(instance_time);
+        }
+
+        if !self.began && self.current_time > 0.0 {
+            self.began = true;
+            self.set_callback(&self.callbacks.begin);
+        }
+        if !self.loop_began && self.current_time > 0.0 {
+            self.loop_began = true;
+            self.set_callback(&self.callbacks.loop_begin);
+        }
+
+        if instance_time <= delay && self.current_time != 0.0 {
+            self.set_animations_progress(0.0);
+        }
+        if (instance_time >= end_delay && self.current_time != duration) || duration == 0.0 {
+            self.set_animations_progress(duration);
+        }
+
+        if instance_time > delay && instance_time < end_delay {
+            if !self.change_began {
+                self.change_began = true;
+                self.change_completed = false;
+                self.set_callback(&self.callbacks.change_begin);
+            }
+            self.set_callback(&self.callbacks.change);
+            self.set_animations_progress(instance_time);
+        } else if self.change_began {
+            self.change_completed = true;
+            self.change_began = false;
+            self.set_callback(&self.callbacks.change_complete);
+        }
+
+        self.current_time = instance_time.clamp(0.0, duration);
+
+        if self.began {
+            self.set_callback(&self.callbacks.update);
+        }
+
+        if engine_time >= duration {
+            self.last_time = 0.0;
+            self.count_iteration();
+            if self.remaining == Some(0) {
+                self.paused = true;
+                if !self.completed {
+                    self.completed = true;
+                    self.set_callback(&self.callbacks.loop_complete);
+                    self.set_callback(&self.callbacks.complete);
+                    #[cfg(feature = "animation-futures")]
+                    if !self.passthrough {
+                        self.completions += 1;
+                        self.wake();
+                    }
+                }
+            } else {
+                self.start_time = Some(self.now);
+                self.set_callback(&self.callbacks.loop_complete);
+                self.loop_began = false;
+                if matches!(self.direction, Direction::Alternate) {
+                    self.toggle_direction();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "animation-futures")]
+impl Instance {
+    /// Number of times this instance has completed
+    pub(crate) fn completions(&self) -> u32 {
+        self.completions
+    }
+
+    /// Wake `waker` the next time this instance completes (or is dropped)
+    pub(crate) fn wake_on_complete(&mut self, waker: &std::task::Waker) {
+        if !self.wakers.iter().any(|w| w.will_wake(waker)) {
+            self.wakers.push(waker.clone());
+        }
+    }
+
+    /// Wake the futures waiting for this instance
+    pub(crate) fn wake(&mut self) {
+        self.wakers.drain(..).for_each(std::task::Waker::wake);
+    }
+}
+
+/// Resolve any waiting futures if dropped while still in the engine (e.g.
+/// along with the engine)
+#[cfg(feature = "animation-futures")]
+impl Drop for Instance {
+    fn drop(&mut self) {
+        self.wake();
     }
 }
 
@@ -234,3 +1071,68 @@ impl Direction {
 }
 
 // TODO: impl future for Animation?
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// From 0 to 100px over a second, starting at `start` after a delay of
+    /// `delay`
+    fn tween(start: f32, delay: f32) -> Tween<f32> {
+        Tween {
+            from: Value::new(0.0, None),
+            to: Value::new(100.0, Some(Unit::Px)),
+            start,
+            end: start + delay + 1000.0,
+            duration: 1000.0,
+            delay,
+            end_delay: 0.0,
+            easing: Easing::Linear,
+            round: 0,
+            interpolation: Interpolation::default(),
+        }
+    }
+
+    #[test]
+    fn evaluates_tweens_over_their_duration() {
+        let tween = tween(0.0, 200.0);
+        assert_eq!(tween.value_at(0.0).to_string(), "0px");
+        assert_eq!(tween.value_at(200.0).to_string(), "0px");
+        assert_eq!(tween.value_at(450.0).to_string(), "25px");
+        assert_eq!(tween.value_at(1200.0).to_string(), "100px");
+        assert_eq!(tween.value_at(5000.0).to_string(), "100px");
+    }
+
+    #[test]
+    fn rounds_tweened_values() {
+        let tween = Tween {
+            round: 10,
+            ..tween(0.0, 0.0)
+        };
+        assert_eq!(tween.value_at(333.3).to_string(), "33.3px");
+    }
+
+    #[test]
+    fn chains_tweens() {
+        let chain = TweenChain {
+            value: PropertyValue::from(0.0),
+            params: TweenParameters::default(),
+            initial: Value::new(0.0, None),
+            default_unit: None,
+            tweens: vec![
+                tween(0.0, 0.0),
+                Tween {
+                    from: Value::new(100.0, Some(Unit::Px)),
+                    to: Value::new(0.0, Some(Unit::Px)),
+                    ..tween(1000.0, 500.0)
+                },
+            ],
+        };
+        assert_eq!(chain.duration(), 2500.0);
+        assert_eq!(chain.value_at(500.0), "50px");
+        // Held through the second tween's delay
+        assert_eq!(chain.value_at(1250.0), "100px");
+        assert_eq!(chain.value_at(2000.0), "50px");
+        assert_eq!(chain.value_at(3000.0), "0px");
+    }
+}
@@ -1,25 +1,103 @@
 use generational_arena::Index;
 
-use crate::target::Target;
-
-use super::{instance::Instance, Engine};
+use super::{instance::Instance, EngineError};
 
 #[derive(Debug, Clone)]
 pub struct Handle {
     engine: super::internals::EngineRef,
     index: Index,
+
+    /// When awaited, the number of completions of the instance at the first
+    /// poll (so we resolve on the next one)
+    #[cfg(feature = "animation-futures")]
+    awaiting: Option<u32>,
 }
 
 impl Handle {
-    /// Delete this animation, removing it from the engine.
+    pub(super) fn new(engine: super::internals::EngineRef, index: Index) -> Self {
+        Self {
+            engine,
+            index,
+            #[cfg(feature = "animation-futures")]
+            awaiting: None,
+        }
+    }
+
+    /// Run `f` on the instance behind this handle, with the engine locked
+    fn with_instance<R, F: FnOnce(&mut Instance) -> R>(&self, f: F) -> Result<R, EngineError> {
+        let mut guard = self.engine.write()?;
+        guard
+            .instances
+            .get_mut(self.index)
+            .map(f)
+            .ok_or(EngineError::StaleHandle)
+    }
+
+    /// Start (or resume) playback
+    pub fn play(&self) -> Result<(), EngineError> {
+        self.with_instance(Instance::play)?;
+        super::internals::run(self.engine.clone())
+    }
+
+    /// Pause playback, keeping the current position
+    pub fn pause(&self) -> Result<(), EngineError> {
+        self.with_instance(Instance::pause)
+    }
+
+    /// Reverse the direction of playback
+    pub fn reverse(&self) -> Result<(), EngineError> {
+        self.with_instance(Instance::reverse)
+    }
+
+    /// Reset to the beginning and start playing
+    pub fn restart(&self) -> Result<(), EngineError> {
+        self.with_instance(Instance::restart)?;
+        super::internals::run(self.engine.clone())
+    }
+
+    /// Jump to `time` milliseconds into the animation. If playing, playback
+    /// continues from there
+    pub fn seek(&self, time: f32) -> Result<(), EngineError> {
+        self.with_instance(|instance| {
+            instance.seek(time);
+            instance.reset_time();
+        })
+    }
+
+    /// Jump to a label placed on a timeline. If playing, playback continues
+    /// from there
+    pub fn seek_label(&self, label: &str) -> Result<(), EngineError> {
+        self.with_instance(|instance| {
+            let time = instance
+                .label(label)
+                .ok_or_else(|| EngineError::UnknownLabel(label.to_string()))?;
+            instance.seek(time);
+            instance.reset_time();
+            Ok(())
+        })?
+    }
+
+    /// Jump to a fraction (`0.0..=1.0`) of the animation's duration
+    pub fn seek_progress(&self, progress: f32) -> Result<(), EngineError> {
+        self.with_instance(|instance| {
+            instance.seek(progress.clamp(0.0, 1.0) * instance.duration());
+            instance.reset_time();
+        })
+    }
+
+    /// Delete this animation, removing it from the engine. Any futures
+    /// waiting on it resolve.
     // TODO: probably doesn't need to consume self, but that likely prevents
     // some misuse as the handle is invalid after this call
     pub async fn remove(self) -> Option<Instance> {
-        if let Ok(mut guard) = self.engine.write() {
-            guard.instances.remove(self.index)
-        } else {
-            None
-        }
+        let instance = self.engine.write().ok()?.instances.remove(self.index);
+        // Once the engine is unlocked, so they can poll it
+        #[cfg(feature = "animation-futures")]
+        let instance = instance.map(|mut instance| {
+            instance.wake();
+            instance
+        });
+        instance
     }
 }
 
@@ -33,12 +111,34 @@ mod future {
 
     use super::Handle;
 
-    // TODO: instead of polling finished, fire with on_complete?
-
+    /// Resolves when the instance completes, or is removed from the engine.
+    ///
+    /// If the instance has already completed when first polled, this resolves
+    /// immediately
     impl Future for Handle {
         type Output = ();
-        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-            todo!()
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let engine = self.engine.clone();
+            let mut guard = match engine.write() {
+                Ok(guard) => guard,
+                // Nothing will ever wake us if the engine is poisoned
+                Err(_) => return Poll::Ready(()),
+            };
+            let instance = match guard.instances.get_mut(self.index) {
+                Some(instance) => instance,
+                None => return Poll::Ready(()),
+            };
+
+            let completions = instance.completions();
+            match self.awaiting {
+                None if instance.completed() => return Poll::Ready(()),
+                Some(awaiting) if completions > awaiting => return Poll::Ready(()),
+                None => self.awaiting = Some(completions),
+                Some(_) => {}
+            }
+
+            instance.wake_on_complete(cx.waker());
+            Poll::Pending
         }
     }
 }
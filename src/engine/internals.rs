@@ -1,22 +1,24 @@
-use std::sync::{Arc, RwLock};
+use std::{rc::Rc, sync::RwLock};
 
-use generational_arena::{Arena};
+use generational_arena::Arena;
 use gloo::events::EventListener;
 use wasm_bindgen::UnwrapThrowExt;
 
-use super::{Instance};
-use crate::{ util::document_is_hidden};
+use super::{
+    clock::{AnimationFrameClock, Clock},
+    Instance, Speed,
+};
 
-use super::Speed;
-
-pub type EngineRef = Arc<RwLock<Engine>>;
+/// The engine is only used from the (single) wasm thread, so is shared with `Rc`
+pub type EngineRef = Rc<RwLock<Engine>>;
 
 pub fn new_ref(opts: super::EngineBuilder) -> EngineRef {
-    Arc::new(RwLock::new(Engine {
+    Rc::new(RwLock::new(Engine {
         visiblity_listener: None,
 
         suspend_while_hidden: opts.suspend_while_hidden.unwrap_or(true),
         speed: opts.speed.unwrap_or(Speed::Normal),
+        clock: opts.clock.unwrap_or_else(|| Box::new(AnimationFrameClock)),
 
         frame: None,
         instances: Arena::new(),
@@ -30,7 +32,10 @@ pub struct Engine {
     suspend_while_hidden: bool,
     pub(super) speed: super::Speed,
 
-    /// When running, this is the last value returned by the browser's `requestAnimationFrame` loop
+    /// Source of animation frames
+    pub(super) clock: Box<dyn Clock>,
+
+    /// When running, this is the last frame identifier returned by the clock
     frame: Option<i32>,
 
     pub(super) instances: Arena<Instance>,
@@ -42,63 +47,87 @@ impl Engine {
     }
 
     pub fn can_run(&self) -> bool {
-        !self.suspend_while_hidden || !document_is_hidden()
+        !self.suspend_while_hidden || !self.clock.document_hidden()
     }
 
-    pub fn step(&mut self, time: i32) {
+    /// Tick all active instances, returning false if there were none (and
+    /// the engine can stop requesting frames)
+    pub fn step(&mut self, time: f32) -> bool {
+        let mut active = false;
         for (_, instance) in self.instances.iter_mut() {
             if instance.active() {
-                instance.tick(time)
+                instance.tick(time, self.speed);
+                active = true;
             }
         }
-
-        // Call next request_animation_frame
+        active
     }
 
-    /// Set up the "visibilitychange" listener (with an external Arc to self)
+    /// Set up the "visibilitychange" listener (with an external reference to self)
     pub fn listen_for_visibility_change(&mut self, engine: EngineRef) {
+        if !self.clock.tracks_visibility() {
+            return;
+        }
+
         self.visiblity_listener = Some(gloo::events::EventListener::new(
             &crate::util::document(),
             "visibilitychange",
             move |_evt| {
-                if let Ok(mut this) = engine.clone().write() {
+                let resume = if let Ok(mut this) = engine.write() {
                     if this.suspend_while_hidden {
-                        if crate::util::document_is_hidden() {
-                            this.frame
-                                .take()
-                                .map(|frame| crate::util::window().cancel_animation_frame(frame))
-                                .expect_throw("Failed to cancel animation");
+                        if this.clock.document_hidden() {
+                            let frame = this.frame.take();
+                            if let Some(frame) = frame {
+                                this.clock.cancel_frame(frame)
+                            }
+                            false
                         } else {
                             for (_, instance) in this.instances.iter_mut() {
                                 instance.on_document_visibility()
                             }
-                            // TODO: scope below?
-                            run(engine.clone()); // TODO: error
+                            true
                         }
+                    } else {
+                        false
                     }
+                } else {
+                    false
+                };
+
+                // Restart outside of the write guard, since `run` takes its own
+                if resume {
+                    let _ = run(engine.clone()); // TODO: error
                 }
             },
         ))
     }
 }
 
-pub(super) fn frame_callback(engine: EngineRef, t: i32) {
-    engine
-        .clone()
-        .write()
-        .expect_throw("Failed to call animation frame")
-        .step(t);
-
+fn request_frame(engine: &EngineRef) -> Result<(), super::EngineError> {
     let engine_ref = engine.clone();
-    if let Ok(frame) =
-        crate::util::request_animation_frame(move |t| frame_callback(engine_ref.clone(), t))
-    {
-        engine
+    let mut guard = engine.write()?;
+    guard.frame = Some(
+        guard
+            .clock
+            .request_frame(Box::new(move |t| frame_callback(engine_ref, t)))?,
+    );
+    Ok(())
+}
+
+pub(super) fn frame_callback(engine: EngineRef, t: f32) {
+    let active = {
+        let mut guard = engine
             .write()
-            .expect_throw("Failed to call animation frame")
-            .frame = Some(frame);
+            .expect_throw("Failed to call animation frame");
+        guard.frame = None;
+        guard.step(t)
+    };
+
+    // Keep going while anything is playing, otherwise wait for `run`
+    if active {
+        // TODO: handle error?
+        let _ = request_frame(&engine);
     }
-    // Else, handle error?
 }
 
 pub(super) fn run(engine: EngineRef) -> Result<(), super::EngineError> {
@@ -107,15 +136,17 @@ pub(super) fn run(engine: EngineRef) -> Result<(), super::EngineError> {
         !inner.running() && inner.can_run()
     };
     if enable {
-        let engine_ref = engine.clone();
-        engine
-            .write()
-            .map_err(|_| super::EngineError::WriteError)?
-            .frame = Some(
-            crate::util::request_animation_frame(move |t| frame_callback(engine_ref.clone(), t))
-                .map_err(|_| super::EngineError::RafFailure)?,
-        );
+        request_frame(&engine)?;
     }
 
     Ok(())
 }
+
+/// Move a manually driven clock forward, running the pending frame (if any)
+pub(super) fn advance(engine: EngineRef, elapsed: f32) -> Result<(), super::EngineError> {
+    let pending = engine.write()?.clock.advance(elapsed);
+    if let Some((callback, now)) = pending {
+        callback(now);
+    }
+    Ok(())
+}
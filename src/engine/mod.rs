@@ -1,15 +1,11 @@
-use std::sync::{Arc, RwLock};
-
-use generational_arena::{Arena, Index};
-use gloo::events::EventListener;
-use wasm_bindgen::UnwrapThrowExt;
-
-use crate::{target::Target, util::document_is_hidden};
-
+pub mod clock;
+pub(crate) mod element;
 mod instance;
 mod interface;
 mod internals;
 
+pub use clock::{Clock, ManualClock};
+pub(crate) use instance::{Animatable, AnimationKind, Callbacks, Tween, TweenChain};
 pub use instance::{Animation, Instance};
 pub use interface::Handle;
 
@@ -32,10 +28,25 @@ pub struct Engine(internals::EngineRef);
 pub struct EngineBuilder {
     pub suspend_while_hidden: Option<bool>,
     pub speed: Option<Speed>,
+
+    /// Source of animation frames. Defaults to
+    /// [`AnimationFrameClock`](clock::AnimationFrameClock)
+    pub clock: Option<Box<dyn Clock>>,
 }
 
 impl EngineBuilder {
-    fn init(self) -> Result<Engine, EngineError> {
+    /// Use the given [`Clock`] to drive the engine
+    pub fn clock<C: 'static + Clock>(mut self, clock: C) -> Self {
+        self.clock = Some(Box::new(clock));
+        self
+    }
+
+    /// Drive the engine with a [`ManualClock`], advanced by [`Engine::advance`]
+    pub fn manual() -> Self {
+        Self::default().clock(ManualClock::new())
+    }
+
+    pub fn init(self) -> Result<Engine, EngineError> {
         let inner = internals::new_ref(self);
 
         // Hold a scoped write guard to set listeners with references to self
@@ -61,6 +72,15 @@ pub enum EngineError {
 
     #[error("Failed to call requestAnimationFrame")]
     RafFailure,
+
+    #[error("Animation instance is no longer in the engine")]
+    StaleHandle,
+
+    #[error("No timeline label named {0:?}")]
+    UnknownLabel(String),
+
+    #[error(transparent)]
+    Target(#[from] crate::target::TargetError),
 }
 
 impl<'a, T> From<std::sync::PoisonError<std::sync::RwLockReadGuard<'a, T>>> for EngineError {
@@ -81,13 +101,13 @@ pub enum Speed {
     Multiplied(f32),
 }
 impl Speed {
-    // pub fn recip(&self) -> f32 {
-    //     if let Speed::Multiplied(speed) = self {
-    //         speed.recip()
-    //     } else {
-    //         1f32
-    //     }
-    // }
+    pub fn multiplier(&self) -> f32 {
+        if let Speed::Multiplied(speed) = self {
+            *speed
+        } else {
+            1f32
+        }
+    }
 }
 
 impl Engine {
@@ -105,14 +125,668 @@ impl Engine {
             .unwrap_or(Speed::Normal)
     }
 
-    // TODO: add Instance instead of
-    pub fn add<T: Into<Target>, I: Into<Instance>>(&self, target: T, animation: I) -> Handle {
-        // TODO
-        todo!()
+    /// Add an instance to the engine, starting it if it autoplays
+    pub fn add(&self, mut instance: Instance) -> Result<Handle, EngineError> {
+        let index = {
+            let mut guard = self.0.write()?;
+            instance.attach(guard.speed);
+            guard.instances.insert(instance)
+        };
+        self.run()?;
+        Ok(Handle::new(self.0.clone(), index))
     }
 
     /// Start the engine if not already running
     pub fn run(&self) -> Result<(), EngineError> {
         internals::run(self.0.clone())
     }
+
+    /// Move the engine's clock forward by `elapsed` milliseconds, running a
+    /// frame if one is pending.
+    ///
+    /// Only has an effect for engines driven by a [`ManualClock`]
+    pub fn advance(&self, elapsed: f32) -> Result<(), EngineError> {
+        internals::advance(self.0.clone(), elapsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::RefCell,
+        future::Future,
+        rc::Rc,
+        task::{Context, Poll, Waker},
+    };
+
+    use super::{
+        element::{Element, StyleMap},
+        *,
+    };
+    use crate::{
+        builder::{property::TweenParameters, AnimationCallback},
+        easings::Easing,
+        properties::{CssProperty, CssTransform, PropertyValue},
+        Direction, Repeat,
+    };
+
+    type Events = Rc<RefCell<Vec<&'static str>>>;
+
+    fn engine() -> Engine {
+        EngineBuilder::manual()
+            .init()
+            .expect("Failed to initialize engine")
+    }
+
+    /// Opacity from 0 to 1 over a second (linearly, without a target),
+    /// recording playback events in `events`
+    fn instance(direction: Direction, repeat: Repeat, autoplay: bool, events: &Events) -> Instance {
+        let params = TweenParameters {
+            easing: Easing::Linear,
+            ..TweenParameters::default()
+        };
+        let animation = CssProperty::Opacity(PropertyValue::from(1.0)).animation(None, &params);
+        let record = |event: &'static str| {
+            let events = events.clone();
+            let callback: Box<dyn AnimationCallback> =
+                Box::new(move |_: &Instance| events.borrow_mut().push(event));
+            Some(callback)
+        };
+        let callbacks = Callbacks {
+            begin: record("begin"),
+            complete: record("complete"),
+            loop_begin: record("loop_begin"),
+            loop_complete: record("loop_complete"),
+            ..Callbacks::default()
+        };
+        Instance::new(
+            vec![animation],
+            direction,
+            repeat,
+            autoplay,
+            callbacks,
+            &params,
+        )
+    }
+
+    /// Read the engine's (only) instance
+    fn with_instance<R>(engine: &Engine, f: impl FnOnce(&Instance) -> R) -> R {
+        let guard = engine.0.read().unwrap();
+        let (_, instance) = guard.instances.iter().next().unwrap();
+        f(instance)
+    }
+
+    /// The value last set by the engine's (only) instance
+    fn opacity(engine: &Engine) -> f32 {
+        with_instance(engine, |instance| {
+            instance.animations()[0]
+                .current_value()
+                .and_then(|value| value.parse().ok())
+                .unwrap()
+        })
+    }
+
+    fn completed(engine: &Engine) -> bool {
+        with_instance(engine, Instance::completed)
+    }
+
+    /// The value last set by the first animation found in the engine's (only)
+    /// instance or, for timelines, its descendants
+    fn nested_opacity(engine: &Engine) -> f32 {
+        fn first(instance: &Instance) -> Option<&Animation> {
+            instance
+                .animations()
+                .first()
+                .or_else(|| instance.children().iter().find_map(first))
+        }
+        with_instance(engine, |instance| {
+            first(instance)
+                .and_then(Animation::current_value)
+                .and_then(|value| value.parse().ok())
+                .unwrap()
+        })
+    }
+
+    /// A timeline with a single child at `offset`
+    fn timeline(direction: Direction, child: Instance, offset: f32) -> Instance {
+        let mut timeline = Instance::timeline(direction, Repeat::default(), true);
+        timeline.add(child, offset);
+        timeline
+    }
+
+    /// Run frames every 250ms for `duration` milliseconds
+    fn play_for(engine: &Engine, duration: f32) {
+        for _ in 0..(duration / 250.0) as u32 {
+            engine.advance(250.0).unwrap();
+        }
+    }
+
+    #[test]
+    fn plays_once_then_completes() {
+        let events = Events::default();
+        let engine = engine();
+        engine
+            .add(instance(
+                Direction::Normal,
+                Repeat::default(),
+                true,
+                &events,
+            ))
+            .unwrap();
+
+        engine.advance(0.0).unwrap();
+        assert_eq!(opacity(&engine), 0.0);
+        assert!(events.borrow().is_empty());
+
+        play_for(&engine, 500.0);
+        assert_eq!(opacity(&engine), 0.5);
+        assert_eq!(*events.borrow(), ["begin", "loop_begin"]);
+
+        play_for(&engine, 500.0);
+        assert_eq!(opacity(&engine), 1.0);
+        assert_eq!(
+            *events.borrow(),
+            ["begin", "loop_begin", "loop_complete", "complete"]
+        );
+        assert!(completed(&engine));
+
+        // The engine stops on the next frame, and nothing else happens
+        play_for(&engine, 500.0);
+        assert!(!engine.running());
+        assert_eq!(opacity(&engine), 1.0);
+        assert_eq!(events.borrow().len(), 4);
+    }
+
+    #[test]
+    fn loops() {
+        let events = Events::default();
+        let engine = engine();
+        let repeat = Repeat::Finite {
+            current: 0,
+            total: 3,
+        };
+        engine
+            .add(instance(Direction::Normal, repeat, true, &events))
+            .unwrap();
+
+        engine.advance(0.0).unwrap();
+        play_for(&engine, 1000.0);
+        assert_eq!(opacity(&engine), 1.0);
+        play_for(&engine, 250.0);
+        assert_eq!(opacity(&engine), 0.25);
+        play_for(&engine, 2750.0);
+        assert_eq!(opacity(&engine), 1.0);
+        assert!(completed(&engine));
+
+        let events = events.borrow();
+        let count = |event| events.iter().filter(|e| **e == event).count();
+        assert_eq!(count("begin"), 1);
+        assert_eq!(count("loop_begin"), 3);
+        assert_eq!(count("loop_complete"), 3);
+        assert_eq!(count("complete"), 1);
+        assert_eq!(events.last(), Some(&"complete"));
+    }
+
+    #[test]
+    fn alternates() {
+        let events = Events::default();
+        let engine = engine();
+        engine
+            .add(instance(
+                Direction::Alternate,
+                Repeat::default(),
+                true,
+                &events,
+            ))
+            .unwrap();
+
+        engine.advance(0.0).unwrap();
+        play_for(&engine, 1000.0);
+        assert_eq!(opacity(&engine), 1.0);
+
+        // Then back again
+        play_for(&engine, 250.0);
+        assert_eq!(opacity(&engine), 0.75);
+        play_for(&engine, 750.0);
+        assert_eq!(opacity(&engine), 0.0);
+        assert!(completed(&engine));
+        assert_eq!(events.borrow().last(), Some(&"complete"));
+    }
+
+    #[test]
+    fn doesnt_play_without_autoplay() {
+        let events = Events::default();
+        let engine = engine();
+        let handle = engine
+            .add(instance(
+                Direction::Normal,
+                Repeat::default(),
+                false,
+                &events,
+            ))
+            .unwrap();
+
+        play_for(&engine, 500.0);
+        assert_eq!(opacity(&engine), 0.0);
+
+        handle.play().unwrap();
+        engine.advance(0.0).unwrap();
+        play_for(&engine, 500.0);
+        assert_eq!(opacity(&engine), 0.5);
+    }
+
+    #[test]
+    fn seeks() {
+        let events = Events::default();
+        let engine = engine();
+        let handle = engine
+            .add(instance(
+                Direction::Normal,
+                Repeat::default(),
+                false,
+                &events,
+            ))
+            .unwrap();
+
+        handle.seek(500.0).unwrap();
+        assert_eq!(opacity(&engine), 0.5);
+        handle.seek_progress(0.25).unwrap();
+        assert_eq!(opacity(&engine), 0.25);
+
+        // Playback continues from the seeked position
+        handle.play().unwrap();
+        engine.advance(0.0).unwrap();
+        play_for(&engine, 250.0);
+        assert_eq!(opacity(&engine), 0.5);
+
+        assert!(matches!(
+            handle.seek_label("missing"),
+            Err(EngineError::UnknownLabel(_))
+        ));
+    }
+
+    #[test]
+    fn reverses() {
+        let events = Events::default();
+        let engine = engine();
+        let handle = engine
+            .add(instance(
+                Direction::Normal,
+                Repeat::default(),
+                true,
+                &events,
+            ))
+            .unwrap();
+
+        engine.advance(0.0).unwrap();
+        play_for(&engine, 750.0);
+        assert_eq!(opacity(&engine), 0.75);
+
+        // Time restarts from the next frame after reversing (as after playing)
+        handle.reverse().unwrap();
+        engine.advance(0.0).unwrap();
+        play_for(&engine, 250.0);
+        assert_eq!(opacity(&engine), 0.5);
+        play_for(&engine, 500.0);
+        assert_eq!(opacity(&engine), 0.0);
+        assert!(completed(&engine));
+        assert_eq!(events.borrow().last(), Some(&"complete"));
+    }
+
+    #[test]
+    fn resolves_relative_values_when_played() {
+        let engine = engine();
+        let element = Rc::new(StyleMap::default());
+        element.set_style("transform", "translateX(50px)");
+
+        let params = TweenParameters {
+            easing: Easing::Linear,
+            ..TweenParameters::default()
+        };
+        let animatable = Rc::new(Animatable::new(element.clone()));
+        let animation =
+            CssTransform::TranslateX("+=100".parse().unwrap()).animation(Some(animatable), &params);
+        let instance = Instance::new(
+            vec![animation],
+            Direction::Normal,
+            Repeat::default(),
+            false,
+            Callbacks::default(),
+            &params,
+        );
+        let handle = engine.add(instance).unwrap();
+
+        // Moved by something else before playing
+        element.set_style("transform", "translateX(100px)");
+        handle.play().unwrap();
+        engine.advance(0.0).unwrap();
+        play_for(&engine, 500.0);
+        assert_eq!(
+            element.get("transform").as_deref(),
+            Some("translateX(150px)")
+        );
+
+        // Restarting resolves against the value at the restart (halfway)
+        handle.restart().unwrap();
+        engine.advance(0.0).unwrap();
+        play_for(&engine, 1000.0);
+        assert_eq!(
+            element.get("transform").as_deref(),
+            Some("translateX(250px)")
+        );
+    }
+
+    #[test]
+    fn starts_from_the_targets_current_values() {
+        let engine = engine();
+        let element = Rc::new(StyleMap::default());
+        element.set_style("opacity", "0.5");
+        element.set_style("transform", "translateX(100px)");
+
+        let params = TweenParameters {
+            easing: Easing::Linear,
+            ..TweenParameters::default()
+        };
+        let animatable = Rc::new(Animatable::new(element.clone()));
+        let animations = vec![
+            CssProperty::Opacity(PropertyValue::from(1.0))
+                .animation(Some(animatable.clone()), &params),
+            CssTransform::TranslateX(PropertyValue::from(200)).animation(Some(animatable), &params),
+        ];
+        let instance = Instance::new(
+            animations,
+            Direction::Normal,
+            Repeat::default(),
+            true,
+            Callbacks::default(),
+            &params,
+        );
+        engine.add(instance).unwrap();
+
+        engine.advance(0.0).unwrap();
+        play_for(&engine, 500.0);
+        assert_eq!(element.get("opacity").as_deref(), Some("0.75"));
+        assert_eq!(
+            element.get("transform").as_deref(),
+            Some("translateX(150px)")
+        );
+    }
+
+    #[test]
+    fn keeps_the_targets_other_transforms() {
+        let engine = engine();
+        let element = Rc::new(StyleMap::default());
+        // Function names are matched case-insensitively
+        element.set_style("transform", "rotate(45deg) TRANSLATEX(100px)");
+
+        let params = TweenParameters {
+            easing: Easing::Linear,
+            ..TweenParameters::default()
+        };
+        let animatable = Rc::new(Animatable::new(element.clone()));
+        let animations = vec![
+            CssTransform::TranslateX(PropertyValue::from(200))
+                .animation(Some(animatable.clone()), &params),
+            CssTransform::TranslateY(PropertyValue::from(50)).animation(Some(animatable), &params),
+        ];
+        let instance = Instance::new(
+            animations,
+            Direction::Normal,
+            Repeat::default(),
+            true,
+            Callbacks::default(),
+            &params,
+        );
+        engine.add(instance).unwrap();
+
+        engine.advance(0.0).unwrap();
+        play_for(&engine, 500.0);
+        assert_eq!(
+            element.get("transform").as_deref(),
+            Some("rotate(45deg) TRANSLATEX(150px) translateY(25px)")
+        );
+    }
+
+    #[test]
+    fn pauses_and_resumes() {
+        let events = Events::default();
+        let engine = engine();
+        let handle = engine
+            .add(instance(
+                Direction::Normal,
+                Repeat::default(),
+                true,
+                &events,
+            ))
+            .unwrap();
+
+        engine.advance(0.0).unwrap();
+        play_for(&engine, 250.0);
+        handle.pause().unwrap();
+        play_for(&engine, 500.0);
+        assert_eq!(opacity(&engine), 0.25);
+        assert!(!engine.running());
+
+        // Continuing from where it was paused
+        handle.play().unwrap();
+        engine.advance(0.0).unwrap();
+        play_for(&engine, 250.0);
+        assert_eq!(opacity(&engine), 0.5);
+    }
+
+    #[test]
+    fn seeks_by_progress() {
+        let events = Events::default();
+        let engine = engine();
+        let handle = engine
+            .add(instance(
+                Direction::Normal,
+                Repeat::default(),
+                false,
+                &events,
+            ))
+            .unwrap();
+
+        handle.seek_progress(0.75).unwrap();
+        assert_eq!(opacity(&engine), 0.75);
+        handle.seek_progress(-1.0).unwrap();
+        assert_eq!(opacity(&engine), 0.0);
+        handle.seek_progress(2.0).unwrap();
+        assert_eq!(opacity(&engine), 1.0);
+    }
+
+    #[test]
+    fn seeks_to_labels() {
+        let events = Events::default();
+        let engine = engine();
+        let child = instance(Direction::Normal, Repeat::default(), false, &events);
+        let mut timeline = timeline(Direction::Normal, child, 200.0);
+        timeline.set_labels(vec![("middle".to_string(), 700.0)]);
+        let handle = engine.add(timeline).unwrap();
+        handle.pause().unwrap();
+
+        handle.seek_label("middle").unwrap();
+        assert_eq!(with_instance(&engine, Instance::current_time), 700.0);
+        assert_eq!(nested_opacity(&engine), 0.5);
+    }
+
+    #[test]
+    fn handles_go_stale_once_removed() {
+        let events = Events::default();
+        let engine = engine();
+        let handle = engine
+            .add(instance(
+                Direction::Normal,
+                Repeat::default(),
+                true,
+                &events,
+            ))
+            .unwrap();
+
+        let mut remove = Box::pin(handle.clone().remove());
+        let removed = match remove
+            .as_mut()
+            .poll(&mut Context::from_waker(Waker::noop()))
+        {
+            Poll::Ready(instance) => instance,
+            Poll::Pending => panic!("removal shouldn't wait"),
+        };
+        assert!(removed.is_some());
+        assert!(matches!(handle.play(), Err(EngineError::StaleHandle)));
+        assert!(matches!(handle.seek(0.0), Err(EngineError::StaleHandle)));
+    }
+
+    #[test]
+    fn maps_time_into_nested_timelines() {
+        let events = Events::default();
+        let engine = engine();
+        let mut inner = timeline(
+            Direction::Normal,
+            instance(Direction::Normal, Repeat::default(), false, &events),
+            0.0,
+        );
+        inner.set_speed(Speed::Multiplied(2.0));
+        let outer = timeline(Direction::Normal, inner, 500.0);
+        assert_eq!(outer.duration(), 1000.0);
+        engine.add(outer).unwrap();
+
+        engine.advance(0.0).unwrap();
+        play_for(&engine, 500.0);
+        assert_eq!(nested_opacity(&engine), 0.0);
+
+        // Twice as fast as the outer timeline
+        play_for(&engine, 250.0);
+        assert_eq!(nested_opacity(&engine), 0.5);
+        play_for(&engine, 250.0);
+        assert_eq!(nested_opacity(&engine), 1.0);
+        assert!(completed(&engine));
+    }
+
+    #[test]
+    fn plays_children_backwards_in_reversed_timelines() {
+        let events = Events::default();
+        let engine = engine();
+        let child = instance(Direction::Normal, Repeat::default(), false, &events);
+        engine
+            .add(timeline(Direction::Reverse, child, 0.0))
+            .unwrap();
+
+        engine.advance(0.0).unwrap();
+        play_for(&engine, 250.0);
+        assert_eq!(nested_opacity(&engine), 0.75);
+        play_for(&engine, 750.0);
+        assert_eq!(nested_opacity(&engine), 0.0);
+        assert!(completed(&engine));
+    }
+
+    #[test]
+    fn plays_reversed_children_forwards_in_reversed_timelines() {
+        let events = Events::default();
+        let engine = engine();
+        let child = instance(Direction::Reverse, Repeat::default(), false, &events);
+        engine
+            .add(timeline(Direction::Reverse, child, 0.0))
+            .unwrap();
+
+        engine.advance(0.0).unwrap();
+        play_for(&engine, 250.0);
+        assert_eq!(nested_opacity(&engine), 0.25);
+        play_for(&engine, 750.0);
+        assert_eq!(nested_opacity(&engine), 1.0);
+        assert!(completed(&engine));
+    }
+
+    #[cfg(feature = "animation-futures")]
+    mod futures {
+        use std::{
+            future::Future,
+            pin::Pin,
+            sync::{
+                atomic::{AtomicBool, Ordering},
+                Arc,
+            },
+            task::{Context, Poll, Wake, Waker},
+        };
+
+        use super::*;
+
+        /// A waker that records whether it was woken
+        #[derive(Default)]
+        struct Flag(AtomicBool);
+
+        impl Wake for Flag {
+            fn wake(self: Arc<Self>) {
+                self.0.store(true, Ordering::SeqCst);
+            }
+        }
+
+        impl Flag {
+            fn woken(&self) -> bool {
+                self.0.load(Ordering::SeqCst)
+            }
+        }
+
+        fn poll<F: Future + Unpin>(future: &mut F, flag: &Arc<Flag>) -> Poll<F::Output> {
+            let waker = Waker::from(flag.clone());
+            Pin::new(future).poll(&mut Context::from_waker(&waker))
+        }
+
+        fn playing(engine: &Engine) -> Handle {
+            let events = Events::default();
+            let instance = instance(Direction::Normal, Repeat::default(), true, &events);
+            engine.add(instance).unwrap()
+        }
+
+        #[test]
+        fn resolves_on_completion() {
+            let engine = engine();
+            let mut handle = playing(&engine);
+            let flag = Arc::new(Flag::default());
+
+            engine.advance(0.0).unwrap();
+            assert_eq!(poll(&mut handle, &flag), Poll::Pending);
+            play_for(&engine, 500.0);
+            assert_eq!(poll(&mut handle, &flag), Poll::Pending);
+            assert!(!flag.woken());
+
+            play_for(&engine, 500.0);
+            assert!(flag.woken());
+            assert_eq!(poll(&mut handle, &flag), Poll::Ready(()));
+        }
+
+        #[test]
+        fn resolves_immediately_once_completed() {
+            let engine = engine();
+            let handle = playing(&engine);
+            engine.advance(0.0).unwrap();
+            play_for(&engine, 1000.0);
+
+            let mut awaiting = handle.clone();
+            assert_eq!(
+                poll(&mut awaiting, &Arc::new(Flag::default())),
+                Poll::Ready(())
+            );
+        }
+
+        #[test]
+        fn resolves_on_removal() {
+            let engine = engine();
+            let handle = playing(&engine);
+            let mut awaiting = handle.clone();
+            let flag = Arc::new(Flag::default());
+
+            engine.advance(0.0).unwrap();
+            assert_eq!(poll(&mut awaiting, &flag), Poll::Pending);
+
+            // Even though the removed instance is kept
+            let mut remove = Box::pin(handle.remove());
+            let instance = match poll(&mut remove, &Arc::new(Flag::default())) {
+                Poll::Ready(instance) => instance,
+                Poll::Pending => panic!("removal shouldn't wait"),
+            };
+            assert!(instance.is_some());
+            assert!(flag.woken());
+            assert_eq!(poll(&mut awaiting, &flag), Poll::Ready(()));
+        }
+    }
 }
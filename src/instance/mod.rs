@@ -0,0 +1,3 @@
+pub mod timeline;
+
+pub use timeline::{Timeline, TimelineOffset, TimelineOffsetError};
@@ -0,0 +1,523 @@
+//! Public interface for building Timelines
+
+use std::{
+    convert::{Infallible, TryFrom, TryInto},
+    str::FromStr,
+};
+
+use wasm_bindgen::UnwrapThrowExt;
+
+use crate::{
+    builder::{
+        property::{Parameters, TweenParameters},
+        Builder,
+    },
+    easings::Easing,
+    engine::{Engine, EngineError, Handle, Instance, Speed},
+    Direction, Repeat,
+};
+
+/// Where a child (or label) is placed on a [`Timeline`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum TimelineOffset {
+    /// Milliseconds from the start of the timeline (e.g. `1200`)
+    Absolute(f32),
+
+    /// Milliseconds after the end of the previously added child (e.g. `"+=200"`
+    /// or, if negative, `"-=600"`)
+    Relative(f32),
+
+    /// Milliseconds after a label (e.g. `"intro"`, `"intro+=100"` or
+    /// `"intro-=100"`). The label must be added to the timeline before it is
+    /// used
+    Label(String, f32),
+}
+
+impl Default for TimelineOffset {
+    /// Directly after the previous child
+    fn default() -> Self {
+        TimelineOffset::Relative(0.0)
+    }
+}
+
+impl From<u32> for TimelineOffset {
+    fn from(offset: u32) -> Self {
+        TimelineOffset::Absolute(offset as f32)
+    }
+}
+
+impl From<f32> for TimelineOffset {
+    fn from(offset: f32) -> Self {
+        TimelineOffset::Absolute(offset)
+    }
+}
+
+impl FromStr for TimelineOffset {
+    type Err = TimelineOffsetError;
+
+    /// Parse an anime.js style offset: `"1200"`, `"+=200"` or `"-=600"`, or a
+    /// label with an optional offset: `"intro"`, `"intro+=100"`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let parse = |n: &str| {
+            n.trim()
+                .parse::<f32>()
+                .map_err(|_| TimelineOffsetError(s.to_string()))
+        };
+
+        // Split off any relative part
+        let (base, delta) = match s.find("+=").or_else(|| s.find("-=")) {
+            Some(i) if s[i..].starts_with('-') => (&s[..i], -parse(&s[i + 2..])?),
+            Some(i) => (&s[..i], parse(&s[i + 2..])?),
+            None => (s, 0.0),
+        };
+        let base = base.trim();
+
+        if base.is_empty() {
+            if delta == 0.0 && !s.contains('=') {
+                return Err(TimelineOffsetError(s.to_string()));
+            }
+            Ok(TimelineOffset::Relative(delta))
+        } else if let Ok(offset) = base.parse::<f32>() {
+            if s.contains('=') {
+                return Err(TimelineOffsetError(s.to_string()));
+            }
+            Ok(TimelineOffset::Absolute(offset))
+        } else {
+            Ok(TimelineOffset::Label(base.to_string(), delta))
+        }
+    }
+}
+
+impl TryFrom<&str> for TimelineOffset {
+    type Error = TimelineOffsetError;
+
+    /// Parse the offset (see [`TimelineOffset::from_str`])
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("invalid timeline offset: {0:?}")]
+pub struct TimelineOffsetError(String);
+
+impl From<Infallible> for TimelineOffsetError {
+    fn from(never: Infallible) -> Self {
+        match never {}
+    }
+}
+
+impl TimelineOffset {
+    /// Place a label (with an optional offset from it)
+    pub fn label<S: Into<String>>(label: S, delta: f32) -> Self {
+        TimelineOffset::Label(label.into(), delta)
+    }
+
+    /// Start time of a child, given the end of the previously added child and
+    /// the labels placed so far
+    fn resolve(&self, previous_end: f32, labels: &[(String, f32)]) -> Result<f32, EngineError> {
+        match self {
+            TimelineOffset::Absolute(offset) => Ok(*offset),
+            TimelineOffset::Relative(delta) => Ok(previous_end + delta),
+            TimelineOffset::Label(label, delta) => labels
+                .iter()
+                .find(|(name, _)| name == label)
+                .map(|(_, position)| position + delta)
+                .ok_or_else(|| EngineError::UnknownLabel(label.clone())),
+        }
+    }
+}
+
+/// Something placed on a timeline, in the order it was added
+enum Entry {
+    Child(Box<Child>, TimelineOffset),
+    Label(String, TimelineOffset),
+}
+
+enum Child {
+    Animation(Builder),
+    Timeline(Timeline),
+    /// Built by a function, for tests without a DOM
+    #[cfg(test)]
+    Instance(Box<dyn FnOnce(&TweenParameters) -> Instance>),
+}
+
+impl Child {
+    fn build(self, defaults: &TweenParameters) -> Result<Instance, EngineError> {
+        match self {
+            Child::Animation(builder) => builder.build(defaults, false),
+            Child::Timeline(timeline) => timeline.build(defaults, false),
+            #[cfg(test)]
+            Child::Instance(build) => Ok(build(defaults)),
+        }
+    }
+}
+
+/// Sequences animations, controlled together as a single [`Handle`]
+///
+/// Tween parameters set on the timeline (duration, delay, easing, ...) are used
+/// by children that don't set their own
+pub struct Timeline {
+    engine: Engine,
+
+    entries: Vec<Entry>,
+
+    defaults: Parameters,
+
+    direction: Option<Direction>,
+    repeat: Option<Repeat>,
+    speed: Option<Speed>,
+}
+
+impl Engine {
+    pub fn timeline(&self) -> Timeline {
+        Timeline::new(self.clone())
+    }
+}
+
+impl Timeline {
+    fn new(engine: Engine) -> Self {
+        Self {
+            engine,
+            entries: Vec::new(),
+            defaults: Parameters::default(),
+            direction: None,
+            repeat: None,
+            speed: None,
+        }
+    }
+
+    /// Add an animation directly after the previously added one
+    pub fn add(&mut self, animation: &mut Builder) -> &mut Self {
+        let child = Child::Animation(animation.take());
+        self.push_child(child, TimelineOffset::default())
+    }
+
+    /// Add an animation at the given offset: a number of milliseconds, a
+    /// parsed [`TimelineOffset`], or a string to parse (e.g. `"+=200"`)
+    pub fn add_at<O>(
+        &mut self,
+        animation: &mut Builder,
+        offset: O,
+    ) -> Result<&mut Self, TimelineOffsetError>
+    where
+        O: TryInto<TimelineOffset>,
+        TimelineOffsetError: From<O::Error>,
+    {
+        let offset = offset.try_into()?;
+        Ok(self.push_child(Child::Animation(animation.take()), offset))
+    }
+
+    /// Nest a timeline directly after the previously added child
+    pub fn add_timeline(&mut self, timeline: &mut Timeline) -> &mut Self {
+        let child = Child::Timeline(timeline.take());
+        self.push_child(child, TimelineOffset::default())
+    }
+
+    /// Nest a timeline at the given offset (see [`Timeline::add_at`]).
+    ///
+    /// The nested timeline's speed and direction are relative to this one, and
+    /// any tween parameters it doesn't set are inherited from this one
+    pub fn add_timeline_at<O>(
+        &mut self,
+        timeline: &mut Timeline,
+        offset: O,
+    ) -> Result<&mut Self, TimelineOffsetError>
+    where
+        O: TryInto<TimelineOffset>,
+        TimelineOffsetError: From<O::Error>,
+    {
+        let offset = offset.try_into()?;
+        Ok(self.push_child(Child::Timeline(timeline.take()), offset))
+    }
+
+    /// Place a label directly after the previously added animation
+    pub fn label<S: Into<String>>(&mut self, name: S) -> &mut Self {
+        self.entries
+            .push(Entry::Label(name.into(), TimelineOffset::default()));
+        self
+    }
+
+    /// Place a label at the given offset (see [`Timeline::add_at`]), so later
+    /// animations can be added relative to it (see [`TimelineOffset::Label`])
+    /// and playback can seek to it (see [`Handle::seek_label`])
+    pub fn label_at<S, O>(&mut self, name: S, offset: O) -> Result<&mut Self, TimelineOffsetError>
+    where
+        S: Into<String>,
+        O: TryInto<TimelineOffset>,
+        TimelineOffsetError: From<O::Error>,
+    {
+        let offset = offset.try_into()?;
+        self.entries.push(Entry::Label(name.into(), offset));
+        Ok(self)
+    }
+
+    fn push_child(&mut self, child: Child, offset: TimelineOffset) -> &mut Self {
+        self.entries.push(Entry::Child(Box::new(child), offset));
+        self
+    }
+
+    /// Build the timeline and add it to the engine, returning a handle to
+    /// control it.
+    ///
+    /// # Panics
+    ///
+    /// If any of the children's targets can't be resolved or the engine can't
+    /// be locked. See [`Timeline::try_finish`]
+    pub fn finish(&mut self) -> Handle {
+        self.try_finish()
+            .expect_throw("Failed to add timeline to the engine")
+    }
+
+    /// Build the timeline and add it to the engine, returning a handle to
+    /// control it
+    pub fn try_finish(&mut self) -> Result<Handle, EngineError> {
+        let timeline = self.take();
+        let engine = timeline.engine.clone();
+        engine.add(timeline.build(&TweenParameters::default(), true)?)
+    }
+
+    /// Take the built up timeline, leaving an empty one in its place
+    fn take(&mut self) -> Timeline {
+        std::mem::replace(self, Timeline::new(self.engine.clone()))
+    }
+
+    /// Build the timeline and its children, using `parent_defaults` for any
+    /// parameters that aren't set
+    fn build(
+        self,
+        parent_defaults: &TweenParameters,
+        autoplay: bool,
+    ) -> Result<Instance, EngineError> {
+        let defaults = self.defaults.resolve(parent_defaults);
+        let mut instance = Instance::timeline(
+            self.direction.unwrap_or(Direction::Normal),
+            self.repeat.unwrap_or_default(),
+            autoplay,
+        );
+        instance.set_speed(self.speed.unwrap_or(Speed::Normal));
+
+        let mut previous_end = 0.0;
+        let mut labels = Vec::new();
+        for entry in self.entries {
+            match entry {
+                Entry::Child(child, offset) => {
+                    let offset = offset.resolve(previous_end, &labels)?;
+                    // Build the child with its targets as the children before
+                    // it leave them at its offset, so it starts from there
+                    instance.seek_passthrough(offset);
+                    let child = child.build(&defaults)?;
+                    instance.add(child, offset);
+                    previous_end = instance
+                        .children()
+                        .last()
+                        .map(Instance::timeline_end)
+                        .unwrap_or(offset);
+                }
+                Entry::Label(name, offset) => {
+                    let position = offset.resolve(previous_end, &labels)?;
+                    // Later labels with the same name replace earlier ones
+                    labels.retain(|(label, _)| *label != name);
+                    labels.push((name, position));
+                }
+            }
+        }
+        instance.set_labels(labels);
+
+        Ok(instance)
+    }
+}
+
+// Timeline parameters
+impl Timeline {
+    /// Default easing for children
+    pub fn easing(&mut self, value: Easing) -> &mut Self {
+        self.defaults.easing = Some(value);
+        self
+    }
+
+    /// Default duration for children
+    pub fn duration(&mut self, value: u32) -> &mut Self {
+        self.defaults.duration = Some(value);
+        self
+    }
+
+    /// Default delay for children
+    pub fn delay(&mut self, value: u32) -> &mut Self {
+        self.defaults.delay = Some(value);
+        self
+    }
+
+    /// Default end delay for children
+    pub fn end_delay(&mut self, value: u32) -> &mut Self {
+        self.defaults.end_delay = Some(value);
+        self
+    }
+
+    /// Default rounding for children
+    pub fn round(&mut self, value: u32) -> &mut Self {
+        self.defaults.round = Some(value);
+        self
+    }
+
+    /// Playback speed, relative to the parent timeline (or the engine)
+    pub fn speed(&mut self, speed: Speed) -> &mut Self {
+        self.speed = Some(speed);
+        self
+    }
+
+    pub fn direction(&mut self, direction: Direction) -> &mut Self {
+        self.direction = Some(direction);
+        self
+    }
+
+    pub fn alternate(&mut self) -> &mut Self {
+        self.direction = Some(Direction::Alternate);
+        self
+    }
+
+    pub fn repeat(&mut self, n_times: u32) -> &mut Self {
+        self.repeat = Some(Repeat::Finite {
+            current: 0,
+            total: n_times,
+        });
+        self
+    }
+
+    pub fn repeat_forever(&mut self) -> &mut Self {
+        self.repeat = Some(Repeat::Infinite);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::{
+        engine::{element::StyleMap, Animatable, Callbacks, EngineBuilder},
+        properties::{CssTransform, PropertyValue},
+    };
+
+    /// Translate `element` to `x`px
+    fn translate_x(element: &Rc<StyleMap>, x: i32) -> Box<Child> {
+        let element = element.clone();
+        Box::new(Child::Instance(Box::new(move |defaults| {
+            let animatable = Rc::new(Animatable::new(element));
+            let animation = CssTransform::TranslateX(PropertyValue::from(x))
+                .animation(Some(animatable), defaults);
+            Instance::new(
+                vec![animation],
+                Direction::Normal,
+                Repeat::default(),
+                false,
+                Callbacks::default(),
+                defaults,
+            )
+        })))
+    }
+
+    #[test]
+    fn parses_offsets() {
+        let parse = |s: &str| TimelineOffset::try_from(s);
+        assert_eq!(parse("1200"), Ok(TimelineOffset::Absolute(1200.0)));
+        assert_eq!(parse("+=200"), Ok(TimelineOffset::Relative(200.0)));
+        assert_eq!(parse("-=600"), Ok(TimelineOffset::Relative(-600.0)));
+        assert_eq!(parse("intro"), Ok(TimelineOffset::label("intro", 0.0)));
+        assert_eq!(
+            parse("intro+=100"),
+            Ok(TimelineOffset::label("intro", 100.0))
+        );
+        assert_eq!(
+            parse("intro-=100"),
+            Ok(TimelineOffset::label("intro", -100.0))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_offsets() {
+        for offset in ["", "+=abc", "-=", "100+=20", "intro+=x"] {
+            assert!(
+                TimelineOffset::try_from(offset).is_err(),
+                "{:?} should be invalid",
+                offset
+            );
+        }
+    }
+
+    #[test]
+    fn resolves_offsets() {
+        let labels = vec![("intro".to_string(), 500.0)];
+        let resolve = |offset: TimelineOffset| offset.resolve(1000.0, &labels).ok();
+        assert_eq!(resolve(TimelineOffset::Absolute(200.0)), Some(200.0));
+        assert_eq!(resolve(TimelineOffset::Relative(-600.0)), Some(400.0));
+        assert_eq!(resolve(TimelineOffset::label("intro", 100.0)), Some(600.0));
+        assert_eq!(resolve(TimelineOffset::label("outro", 0.0)), None);
+    }
+
+    #[test]
+    fn adds_at_parsed_offsets() {
+        let engine = EngineBuilder::manual().init().unwrap();
+        let mut timeline = engine.timeline();
+        let mut animation = engine.animate("#a");
+        timeline
+            .add_at(&mut animation, 100)
+            .unwrap()
+            .label_at("intro", "+=200")
+            .unwrap()
+            .add_at(&mut animation, "intro-=50")
+            .unwrap();
+        assert!(timeline.add_at(&mut animation, "+=x").is_err());
+
+        let offsets: Vec<_> = timeline
+            .entries
+            .iter()
+            .map(|entry| match entry {
+                Entry::Child(_, offset) | Entry::Label(_, offset) => offset.clone(),
+            })
+            .collect();
+        assert_eq!(
+            offsets,
+            [
+                TimelineOffset::Absolute(100.0),
+                TimelineOffset::Relative(200.0),
+                TimelineOffset::label("intro", -50.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn starts_children_where_the_previous_ones_leave_their_targets() {
+        let engine = EngineBuilder::manual().init().unwrap();
+        let element = Rc::new(StyleMap::default());
+        let mut timeline = engine.timeline();
+        timeline.easing(Easing::Linear).duration(1000);
+        for x in [250, 0] {
+            timeline.entries.push(Entry::Child(
+                translate_x(&element, x),
+                TimelineOffset::default(),
+            ));
+        }
+        timeline.finish();
+
+        let transform = || element.get("transform").unwrap();
+        assert_eq!(transform(), "translateX(0px)");
+        engine.advance(0.0).unwrap();
+        let mut frames = Vec::new();
+        for _ in 0..4 {
+            engine.advance(500.0).unwrap();
+            frames.push(transform());
+        }
+        assert_eq!(
+            frames,
+            [
+                "translateX(125px)",
+                "translateX(250px)",
+                "translateX(125px)",
+                "translateX(0px)"
+            ]
+        );
+    }
+}
@@ -4,12 +4,22 @@ mod builder;
 pub mod css;
 mod easings;
 mod engine;
+mod instance;
 mod path;
 mod properties;
+mod stagger;
 mod target;
 mod util;
 
-pub use engine::Engine;
+pub use engine::{clock, Engine, EngineBuilder, EngineError, Handle, Speed};
+pub use instance::{Timeline, TimelineOffset, TimelineOffsetError};
+pub use properties::{
+    Angle, ColorSpace, ComplexToken, ComplexValue, ComplexValueError, HueDirection, Interpolation,
+    Orientation, PropertyValue, RotationMode, ValueParseError,
+};
+pub use stagger::{
+    stagger, Stagger, StaggerDirection, StaggerFrom, StaggerGridAxis, StaggerProps, Staggerable,
+};
 
 /// Get a handle to the default global engine
 pub fn engine() -> Engine {
@@ -20,47 +30,25 @@ pub fn animate<T: Into<target::Target>>(target: T) -> builder::Builder {
     engine::global().animate(target)
 }
 
+/// Sequence animations on the default global engine
+pub fn timeline() -> instance::Timeline {
+    engine::global().timeline()
+}
+
 pub mod prelude {
 
     pub use palette;
 
-    pub use super::animate;
+    pub use super::{animate, stagger, timeline};
+    pub use crate::builder::keyframe::AnimationKeyframe;
     pub use crate::builder::property::Property;
     pub use crate::easings::{helpers::*, Direction as EasingDirection, Easing};
-    pub use crate::properties::{Unit, Value, ValueOp};
-}
-
-// TODO: Mod
-
-pub enum Stagger<T> {
-    Value(T, Option<StaggerProps>),
-
-    Range(T, T),
-}
-pub struct StaggerProps {
-    start: Option<u32>,
-    from: Option<StaggerFrom>,
-    direction: Option<StaggerDirection>,
-    // easing: Option<Easing>,
-    grid: Option<(u32, u32)>,
-    axis: Option<StaggerGridAxis>,
-}
-
-pub enum StaggerFrom {
-    First, // default
-    Last,
-    Center,
-    Index(u32),
-}
-
-pub enum StaggerDirection {
-    Normal, // default
-    Reverse,
-}
-
-pub enum StaggerGridAxis {
-    X,
-    Y,
+    pub use crate::instance::TimelineOffset;
+    pub use crate::properties::{
+        Angle, ColorSpace, ComplexValue, HueDirection, Orientation, PropertyKeyframe, RotationMode,
+        Unit, Value, ValueOp,
+    };
+    pub use crate::stagger::{StaggerFrom, StaggerProps};
 }
 
 // TODO: move below to animation::parameters
@@ -80,14 +68,19 @@ pub enum Repeat {
 impl Repeat {
     fn remaining(&self) -> Option<u32> {
         if let Repeat::Finite { current, total } = self {
-            Some(total - current)
+            Some(total.saturating_sub(*current))
         } else {
             None
         }
     }
-    fn add(&mut self, repetitions: u32) {
-        if let Repeat::Finite { current, total } = self {
-            *total += repetitions
+}
+
+impl Default for Repeat {
+    /// Play once
+    fn default() -> Self {
+        Repeat::Finite {
+            current: 0,
+            total: 1,
         }
     }
 }
@@ -1,4 +1,5 @@
-use std::ops::Sub;
+// Paths aren't animated yet
+#![allow(dead_code)]
 
 // Path animation
 
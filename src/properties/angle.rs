@@ -0,0 +1,305 @@
+use super::{value::parse_number, Interpolation, Tweenable, Unit, Value};
+
+/// Clockwise Angle
+///
+/// Angles keep the unit they're given in, so tweens are written in the unit of
+/// their end value (with the start converted to it)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Angle {
+    Degrees(f32),
+    Gradians(f32),
+    Radians(f32),
+    Turns(f32),
+}
+
+impl Angle {
+    /// An angle in one of the angle units (`deg`, `grad`, `rad` or `turn`)
+    pub fn from_unit(value: f32, unit: Unit) -> Option<Self> {
+        match unit {
+            Unit::Deg => Some(Angle::Degrees(value)),
+            Unit::Grad => Some(Angle::Gradians(value)),
+            Unit::Rad => Some(Angle::Radians(value)),
+            Unit::Turn => Some(Angle::Turns(value)),
+            _ => None,
+        }
+    }
+
+    /// The angle as a number, in its own unit
+    pub fn value(&self) -> f32 {
+        match *self {
+            Angle::Degrees(value)
+            | Angle::Gradians(value)
+            | Angle::Radians(value)
+            | Angle::Turns(value) => value,
+        }
+    }
+
+    pub fn unit(&self) -> Unit {
+        match self {
+            Angle::Degrees(_) => Unit::Deg,
+            Angle::Gradians(_) => Unit::Grad,
+            Angle::Radians(_) => Unit::Rad,
+            Angle::Turns(_) => Unit::Turn,
+        }
+    }
+
+    /// The same angle in another unit
+    pub fn to_unit(&self, unit: Unit) -> Option<Self> {
+        let factor = Unit::factor(self.unit(), unit)?;
+        Angle::from_unit(self.value() * factor, unit)
+    }
+
+    pub fn to_degrees(&self) -> f32 {
+        self.value() * Unit::factor(self.unit(), Unit::Deg).unwrap_or(1.0)
+    }
+
+    /// A different angle in the same unit
+    fn with_value(&self, value: f32) -> Self {
+        Angle::from_unit(value, self.unit()).unwrap_or(Angle::Degrees(value))
+    }
+
+    /// `other` as a number in this angle's unit
+    fn value_of(&self, other: &Angle) -> f32 {
+        other.to_unit(self.unit()).unwrap_or(*other).value()
+    }
+}
+
+/// Which way to rotate from one angle to another
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RotationMode {
+    /// From one value to the other, however many turns apart (e.g. `350deg`
+    /// to `10deg` rotates 340° counter-clockwise)
+    #[default]
+    Literal,
+    /// The shortest way around, ignoring whole turns (`350deg` to `10deg`
+    /// rotates 20° clockwise)
+    Shortest,
+    /// Always clockwise (increasing). Whole turns in this direction are kept
+    Clockwise,
+    /// Always counter-clockwise (decreasing). Whole turns in this direction
+    /// are kept
+    CounterClockwise,
+}
+
+impl RotationMode {
+    /// The value to interpolate `from` towards to reach `to` in this mode,
+    /// given the size of a whole turn
+    pub(crate) fn end(self, from: f32, to: f32, turn: f32) -> f32 {
+        let delta = to - from;
+        let delta = match self {
+            RotationMode::Literal => delta,
+            RotationMode::Shortest => {
+                let delta = delta.rem_euclid(turn);
+                if delta > turn / 2.0 {
+                    delta - turn
+                } else {
+                    delta
+                }
+            }
+            RotationMode::Clockwise if delta < 0.0 => delta.rem_euclid(turn),
+            RotationMode::CounterClockwise if delta > 0.0 => match delta.rem_euclid(turn) {
+                remainder if remainder > 0.0 => remainder - turn,
+                _ => 0.0,
+            },
+            RotationMode::Clockwise | RotationMode::CounterClockwise => delta,
+        };
+        from + delta
+    }
+}
+
+impl Default for Angle {
+    fn default() -> Self {
+        Angle::Degrees(0.0)
+    }
+}
+
+impl Tweenable for Angle {
+    /// Interpolates in the unit of `to`
+    fn interpolate(from: &Self, to: &Self, t: f32) -> Self {
+        to.with_value(f32::interpolate(&to.value_of(from), &to.value(), t))
+    }
+
+    fn interpolate_with(from: &Self, to: &Self, t: f32, options: &Interpolation) -> Self {
+        let rotation = options.rotation.unwrap_or_default();
+        let from = to.value_of(from);
+        let turn = to.value_of(&Angle::Turns(1.0));
+        let end = rotation.end(from, to.value(), turn);
+        to.with_value(f32::interpolate(&from, &end, t))
+    }
+
+    fn round(&self, round: u32) -> Self {
+        self.with_value(Tweenable::round(&self.value(), round))
+    }
+
+    fn fmt_css(&self, _unit: Option<Unit>, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.value(), self.unit().as_str())
+    }
+
+    /// Numbers without a unit are taken as degrees
+    fn parse_css(text: &str) -> Option<Value<Self>> {
+        let (number, unit) = parse_number(text)?;
+        Some(Value::from(Angle::from_unit(
+            number,
+            unit.unwrap_or(Unit::Deg),
+        )?))
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        self.with_value(self.value() + self.value_of(other))
+    }
+
+    fn subtract(&self, other: &Self) -> Self {
+        self.with_value(self.value() - self.value_of(other))
+    }
+
+    fn multiply(&self, other: &Self) -> Self {
+        self.with_value(self.value() * other.value())
+    }
+
+    fn scale(&self, factor: f32) -> Self {
+        self.with_value(self.value() * factor)
+    }
+}
+
+/// Degrees
+impl From<f32> for Value<Angle> {
+    fn from(degrees: f32) -> Self {
+        Value::from(Angle::Degrees(degrees))
+    }
+}
+
+/// Degrees
+impl From<i32> for Value<Angle> {
+    fn from(degrees: i32) -> Self {
+        Value::from(Angle::Degrees(degrees as f32))
+    }
+}
+
+/// Units other than angles are taken as degrees
+impl From<(f32, Unit)> for Value<Angle> {
+    fn from((value, unit): (f32, Unit)) -> Self {
+        Value::from(Angle::from_unit(value, unit).unwrap_or(Angle::Degrees(value)))
+    }
+}
+
+/// Units other than angles are taken as degrees
+impl From<(i32, Unit)> for Value<Angle> {
+    fn from((value, unit): (i32, Unit)) -> Self {
+        Value::from((value as f32, unit))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: Angle, b: Angle) {
+        assert!(
+            a.unit() == b.unit() && (a.value() - b.value()).abs() < 1e-5,
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }
+
+    #[test]
+    fn converts_between_units() {
+        assert_close(
+            Angle::Degrees(180.0).to_unit(Unit::Turn).unwrap(),
+            Angle::Turns(0.5),
+        );
+        assert_close(
+            Angle::Degrees(90.0).to_unit(Unit::Grad).unwrap(),
+            Angle::Gradians(100.0),
+        );
+        assert_close(
+            Angle::Turns(0.5).to_unit(Unit::Rad).unwrap(),
+            Angle::Radians(std::f32::consts::PI),
+        );
+        assert_eq!(Angle::Turns(1.0).to_unit(Unit::Px), None);
+        assert_eq!(Angle::Radians(std::f32::consts::PI).to_degrees(), 180.0);
+    }
+
+    #[test]
+    fn parses_css_angles() {
+        let parse = |s: &str| Angle::parse_css(s).map(|value| *value.value());
+        assert_eq!(parse("45deg"), Some(Angle::Degrees(45.0)));
+        assert_eq!(parse("0.5turn"), Some(Angle::Turns(0.5)));
+        assert_eq!(parse("-1.5rad"), Some(Angle::Radians(-1.5)));
+        assert_eq!(parse("200grad"), Some(Angle::Gradians(200.0)));
+        // Unitless numbers are degrees
+        assert_eq!(parse("45"), Some(Angle::Degrees(45.0)));
+        assert_eq!(parse("10px"), None);
+    }
+
+    #[test]
+    fn interpolates_in_the_end_unit() {
+        let halfway = |from, to, rotation| {
+            let options = Interpolation {
+                rotation,
+                ..Interpolation::default()
+            };
+            Angle::interpolate_with(&from, &to, 0.5, &options)
+        };
+        assert_close(
+            halfway(Angle::Degrees(90.0), Angle::Turns(0.5), None),
+            Angle::Turns(0.375),
+        );
+        assert_close(
+            halfway(Angle::Turns(0.5), Angle::Radians(0.0), None),
+            Angle::Radians(std::f32::consts::FRAC_PI_2),
+        );
+        // Turns are measured in the end unit
+        assert_close(
+            halfway(
+                Angle::Degrees(270.0),
+                Angle::Turns(0.0),
+                Some(RotationMode::Shortest),
+            ),
+            Angle::Turns(0.875),
+        );
+        assert_close(
+            halfway(
+                Angle::Degrees(0.0),
+                Angle::Gradians(300.0),
+                Some(RotationMode::Shortest),
+            ),
+            Angle::Gradians(-50.0),
+        );
+    }
+
+    #[test]
+    fn rotates_by_mode() {
+        let end = |mode: RotationMode, from, to| mode.end(from, to, 360.0);
+
+        // 20° apart clockwise, 340° counter-clockwise
+        assert_eq!(end(RotationMode::Literal, 350.0, 10.0), 10.0);
+        assert_eq!(end(RotationMode::Shortest, 350.0, 10.0), 370.0);
+        assert_eq!(end(RotationMode::Clockwise, 350.0, 10.0), 370.0);
+        assert_eq!(end(RotationMode::CounterClockwise, 350.0, 10.0), 10.0);
+        // 340° apart clockwise, 20° counter-clockwise
+        assert_eq!(end(RotationMode::Literal, 10.0, 350.0), 350.0);
+        assert_eq!(end(RotationMode::Shortest, 10.0, 350.0), -10.0);
+        assert_eq!(end(RotationMode::Clockwise, 10.0, 350.0), 350.0);
+        assert_eq!(end(RotationMode::CounterClockwise, 10.0, 350.0), -10.0);
+
+        // Whole turns are kept literally, or in the mode's direction
+        assert_eq!(end(RotationMode::Literal, 0.0, 720.0), 720.0);
+        assert_eq!(end(RotationMode::Literal, 0.0, -720.0), -720.0);
+        assert_eq!(end(RotationMode::Clockwise, 0.0, 720.0), 720.0);
+        assert_eq!(end(RotationMode::Clockwise, 0.0, -720.0), 0.0);
+        assert_eq!(end(RotationMode::CounterClockwise, 0.0, -720.0), -720.0);
+        assert_eq!(end(RotationMode::CounterClockwise, 0.0, 720.0), 0.0);
+        assert_eq!(end(RotationMode::Shortest, 0.0, 720.0), 0.0);
+
+        // Exactly a turn apart
+        for (from, to) in [(0.0, 360.0), (0.0, -360.0)] {
+            assert_eq!(end(RotationMode::Literal, from, to), to);
+            assert_eq!(end(RotationMode::Shortest, from, to), 0.0);
+        }
+        assert_eq!(end(RotationMode::Clockwise, 0.0, 360.0), 360.0);
+        assert_eq!(end(RotationMode::Clockwise, 0.0, -360.0), 0.0);
+        assert_eq!(end(RotationMode::CounterClockwise, 0.0, -360.0), -360.0);
+        assert_eq!(end(RotationMode::CounterClockwise, 0.0, 360.0), 0.0);
+    }
+}
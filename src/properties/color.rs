@@ -0,0 +1,278 @@
+//! Interpolating colors in different color spaces
+
+use palette::{FromColor, Hsl, Lab, Lch, LinSrgb, Oklab, Srgb};
+
+use super::{angle::RotationMode, Color, Interpolation, Tweenable};
+
+/// The color space a color is interpolated in
+///
+/// See https://www.w3.org/TR/css-color-4/#interpolation-space
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ColorSpace {
+    /// Gamma encoded sRGB, as browsers interpolate CSS transitions
+    #[default]
+    Srgb,
+    /// Linear (light intensity) sRGB
+    LinearRgb,
+    Hsl(HueDirection),
+    Lab,
+    Lch(HueDirection),
+    Oklab,
+}
+
+/// Which way around the color wheel to interpolate hues
+///
+/// See https://www.w3.org/TR/css-color-4/#hue-interpolation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HueDirection {
+    /// The shortest way around (at most 180°)
+    #[default]
+    Shorter,
+    /// The longest way around (at least 180°)
+    Longer,
+    Increasing,
+    Decreasing,
+}
+
+/// The color at (eased) progress `t` between `from` and `to`, interpolating
+/// in the color space of `options` (with hues going around as set by its
+/// rotation mode, if it has one). Alpha is always interpolated linearly
+pub(crate) fn interpolate(from: &Color, to: &Color, t: f32, options: &Interpolation) -> Color {
+    let alpha = f32::interpolate(&from.alpha, &to.alpha, t).clamp(0.0, 1.0);
+    let (a, b) = (
+        from.color.into_format::<f32>(),
+        to.color.into_format::<f32>(),
+    );
+    let lerp = |a: f32, b: f32| f32::interpolate(&a, &b, t);
+
+    let hue = |from: f32, to: f32, direction: HueDirection| match options.rotation {
+        Some(rotation) => rotated_hue(from, to, t, rotation),
+        None => hue(from, to, t, direction),
+    };
+
+    let color: Srgb = match options.color_space {
+        ColorSpace::Srgb => {
+            let channel = |a: u8, b: u8| lerp(a as f32, b as f32).round().clamp(0.0, 255.0) as u8;
+            return Color::new(
+                channel(from.red, to.red),
+                channel(from.green, to.green),
+                channel(from.blue, to.blue),
+                alpha,
+            );
+        }
+        ColorSpace::LinearRgb => {
+            let (a, b) = (a.into_linear(), b.into_linear());
+            let color = LinSrgb::new(
+                lerp(a.red, b.red),
+                lerp(a.green, b.green),
+                lerp(a.blue, b.blue),
+            );
+            Srgb::from_linear(color)
+        }
+        ColorSpace::Hsl(direction) => {
+            let (a, b): (Hsl, Hsl) = (Hsl::from_color(a), Hsl::from_color(b));
+            let (from_hue, to_hue) = hues(
+                (a.hue.to_positive_degrees(), a.saturation),
+                (b.hue.to_positive_degrees(), b.saturation),
+            );
+            Srgb::from_color(Hsl::new(
+                hue(from_hue, to_hue, direction),
+                lerp(a.saturation, b.saturation),
+                lerp(a.lightness, b.lightness),
+            ))
+        }
+        ColorSpace::Lab => {
+            let (a, b): (Lab, Lab) = (Lab::from_color(a), Lab::from_color(b));
+            Srgb::from_color(Lab::new(lerp(a.l, b.l), lerp(a.a, b.a), lerp(a.b, b.b)))
+        }
+        ColorSpace::Lch(direction) => {
+            let (a, b): (Lch, Lch) = (Lch::from_color(a), Lch::from_color(b));
+            let (from_hue, to_hue) = hues(
+                (a.hue.to_positive_degrees(), a.chroma),
+                (b.hue.to_positive_degrees(), b.chroma),
+            );
+            Srgb::from_color(Lch::new(
+                lerp(a.l, b.l),
+                lerp(a.chroma, b.chroma),
+                hue(from_hue, to_hue, direction),
+            ))
+        }
+        ColorSpace::Oklab => {
+            let (a, b): (Oklab, Oklab) = (Oklab::from_color(a), Oklab::from_color(b));
+            Srgb::from_color(Oklab::new(lerp(a.l, b.l), lerp(a.a, b.a), lerp(a.b, b.b)))
+        }
+    };
+
+    let channel = |c: f32| (c * 255.0).round().clamp(0.0, 255.0) as u8;
+    Color::new(
+        channel(color.red),
+        channel(color.green),
+        channel(color.blue),
+        alpha,
+    )
+}
+
+/// Hues to interpolate between, given `(hue, saturation or chroma)` pairs.
+/// Greys have no real hue, so take the other color's (rather than passing
+/// through red on the way to or from grey)
+fn hues((a, a_chroma): (f32, f32), (b, b_chroma): (f32, f32)) -> (f32, f32) {
+    const ACHROMATIC: f32 = 1e-4;
+    match (a_chroma < ACHROMATIC, b_chroma < ACHROMATIC) {
+        (true, false) => (b, b),
+        (false, true) => (a, a),
+        _ => (a, b),
+    }
+}
+
+/// The hue (in degrees) at progress `t` from `from` to `to`, going around the
+/// color wheel in `direction`
+fn hue(from: f32, to: f32, t: f32, direction: HueDirection) -> f32 {
+    let delta = to.rem_euclid(360.0) - from.rem_euclid(360.0);
+    let delta = match direction {
+        HueDirection::Shorter if delta > 180.0 => delta - 360.0,
+        HueDirection::Shorter if delta < -180.0 => delta + 360.0,
+        HueDirection::Longer if delta > 0.0 && delta < 180.0 => delta - 360.0,
+        HueDirection::Longer if delta > -180.0 && delta <= 0.0 => delta + 360.0,
+        HueDirection::Increasing if delta < 0.0 => delta + 360.0,
+        HueDirection::Decreasing if delta > 0.0 => delta - 360.0,
+        _ => delta,
+    };
+    (from.rem_euclid(360.0) + delta * t).rem_euclid(360.0)
+}
+
+/// The hue (in degrees) at progress `t` from `from` to `to`, going around the
+/// color wheel as set by `rotation` (increasing hues are clockwise)
+fn rotated_hue(from: f32, to: f32, t: f32, rotation: RotationMode) -> f32 {
+    let to = rotation.end(from, to, 360.0);
+    f32::interpolate(&from, &to, t).rem_euclid(360.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: (u8, u8, u8) = (255, 0, 0);
+    const BLUE: (u8, u8, u8) = (0, 0, 255);
+
+    /// The color (as rgb) at progress `t` between opaque `from` and `to`
+    fn at(
+        from: (u8, u8, u8),
+        to: (u8, u8, u8),
+        t: f32,
+        color_space: ColorSpace,
+        rotation: Option<RotationMode>,
+    ) -> (u8, u8, u8) {
+        let color = |(r, g, b)| Color::new(r, g, b, 1.0);
+        let options = Interpolation {
+            color_space,
+            rotation,
+        };
+        let mixed = interpolate(&color(from), &color(to), t, &options);
+        (mixed.red, mixed.green, mixed.blue)
+    }
+
+    #[test]
+    fn interpolates_in_each_color_space() {
+        let midpoint = |color_space| at(RED, BLUE, 0.5, color_space, None);
+        assert_eq!(midpoint(ColorSpace::Srgb), (128, 0, 128));
+        assert_eq!(midpoint(ColorSpace::LinearRgb), (188, 0, 188));
+        assert_eq!(
+            midpoint(ColorSpace::Hsl(HueDirection::Shorter)),
+            (255, 0, 255)
+        );
+        assert_eq!(midpoint(ColorSpace::Lab), (202, 0, 136));
+        assert_eq!(
+            midpoint(ColorSpace::Lch(HueDirection::Shorter)),
+            (250, 0, 128)
+        );
+        assert_eq!(midpoint(ColorSpace::Oklab), (140, 83, 162));
+    }
+
+    #[test]
+    fn goes_around_the_hue_wheel_in_each_direction() {
+        // Red (0°) to blue (240°), through magenta (300°) or green (120°)
+        let midpoint = |direction| at(RED, BLUE, 0.5, ColorSpace::Hsl(direction), None);
+        assert_eq!(midpoint(HueDirection::Shorter), (255, 0, 255));
+        assert_eq!(midpoint(HueDirection::Longer), (0, 255, 0));
+        assert_eq!(midpoint(HueDirection::Increasing), (0, 255, 0));
+        assert_eq!(midpoint(HueDirection::Decreasing), (255, 0, 255));
+
+        for (from, to) in [(350.0, 10.0), (10.0, 350.0)] {
+            let halfway = |direction| hue(from, to, 0.5, direction);
+            assert_eq!(halfway(HueDirection::Shorter), 0.0);
+            assert_eq!(halfway(HueDirection::Longer), 180.0);
+        }
+        assert_eq!(hue(350.0, 10.0, 0.5, HueDirection::Increasing), 0.0);
+        assert_eq!(hue(350.0, 10.0, 0.5, HueDirection::Decreasing), 180.0);
+        assert_eq!(hue(10.0, 350.0, 0.5, HueDirection::Increasing), 180.0);
+        assert_eq!(hue(10.0, 350.0, 0.5, HueDirection::Decreasing), 0.0);
+
+        // Equal hues go all the way around the longer way, and nowhere otherwise
+        assert_eq!(hue(90.0, 90.0, 0.5, HueDirection::Longer), 270.0);
+        for direction in [
+            HueDirection::Shorter,
+            HueDirection::Increasing,
+            HueDirection::Decreasing,
+        ] {
+            assert_eq!(hue(90.0, 90.0, 0.5, direction), 90.0);
+        }
+    }
+
+    #[test]
+    fn greys_take_the_other_colors_hue() {
+        assert_eq!(hues((120.0, 0.0), (240.0, 0.5)), (240.0, 240.0));
+        assert_eq!(hues((120.0, 0.5), (240.0, 0.0)), (120.0, 120.0));
+        assert_eq!(hues((120.0, 0.0), (240.0, 0.0)), (120.0, 240.0));
+        assert_eq!(hues((120.0, 0.5), (240.0, 0.5)), (120.0, 240.0));
+
+        // So grey to red stays red, rather than passing through other hues
+        let grey = (128, 128, 128);
+        let midpoint = at(grey, RED, 0.5, ColorSpace::Hsl(HueDirection::Shorter), None);
+        assert_eq!(midpoint, (191, 64, 64));
+    }
+
+    #[test]
+    fn interpolates_alpha_linearly() {
+        let (from, to) = (Color::new(255, 0, 0, 0.0), Color::new(0, 0, 255, 1.0));
+        for color_space in [
+            ColorSpace::Srgb,
+            ColorSpace::Hsl(HueDirection::Shorter),
+            ColorSpace::Oklab,
+        ] {
+            let options = Interpolation {
+                color_space,
+                rotation: None,
+            };
+            assert_eq!(interpolate(&from, &to, 0.25, &options).alpha, 0.25);
+        }
+    }
+
+    #[test]
+    fn rotates_hues_by_rotation_mode() {
+        let halfway = |from, to, rotation| rotated_hue(from, to, 0.5, rotation);
+        assert_eq!(halfway(350.0, 10.0, RotationMode::Literal), 180.0);
+        assert_eq!(halfway(350.0, 10.0, RotationMode::Shortest), 0.0);
+        assert_eq!(halfway(350.0, 10.0, RotationMode::Clockwise), 0.0);
+        assert_eq!(halfway(350.0, 10.0, RotationMode::CounterClockwise), 180.0);
+        assert_eq!(halfway(10.0, 350.0, RotationMode::Literal), 180.0);
+        assert_eq!(halfway(10.0, 350.0, RotationMode::Shortest), 0.0);
+        assert_eq!(halfway(10.0, 350.0, RotationMode::Clockwise), 180.0);
+        assert_eq!(halfway(10.0, 350.0, RotationMode::CounterClockwise), 0.0);
+    }
+
+    #[test]
+    fn rotation_overrides_the_hue_direction() {
+        let clockwise = Some(RotationMode::Clockwise);
+        let shorter = ColorSpace::Hsl(HueDirection::Shorter);
+        assert_eq!(at(RED, BLUE, 0.5, shorter, clockwise), (0, 255, 0));
+        // And does nothing without hues
+        assert_eq!(
+            at(RED, BLUE, 0.5, ColorSpace::Srgb, clockwise),
+            (128, 0, 128)
+        );
+        assert_eq!(
+            at(RED, BLUE, 0.5, ColorSpace::Oklab, clockwise),
+            (140, 83, 162)
+        );
+    }
+}
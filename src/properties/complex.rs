@@ -0,0 +1,317 @@
+//! Values made of several numbers and colors in a string template (e.g.
+//! `box-shadow: 0 0 10px rgba(0, 0, 0, 0.5)`)
+
+use std::fmt::Display;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use super::{color, Color, Interpolation, Tweenable, Unit, Value};
+
+lazy_static! {
+    static ref COMPLEX_REGEX: Regex = Regex::new(
+        r"(?P<color>#[0-9a-fA-F]{3,8}\b|(?:rgba?|hsla?)\([^)]*\))|(?P<number>[+-]?\d*\.?\d+(?:[eE][+-]?\d+)?)(?P<unit>%|[a-zA-Z]+)?|(?P<word>[a-zA-Z][\w-]*)"
+    ).expect("Failed to compile COMPLEX_REGEX");
+}
+
+/// A string of numbers, colors and literal text, interpolated by tweening
+/// each number and color with the one in the same position of the other
+/// value. Numbers without a unit take the other value's unit
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ComplexValue(Vec<ComplexToken>);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ComplexToken {
+    Number(Value<f32>),
+    Color(Color),
+    Literal(String),
+}
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("incompatible values: {0:?} and {1:?}")]
+pub struct ComplexValueError(String, String);
+
+impl ComplexValue {
+    /// Split `text` into numbers, colors and the literal text around them.
+    /// Runs of whitespace are collapsed to a single space
+    pub fn parse(text: &str) -> Self {
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let mut tokens = Vec::new();
+        let mut literal = String::new();
+        let mut last = 0;
+
+        for captures in COMPLEX_REGEX.captures_iter(&text) {
+            let whole = captures.get(0).expect("capture 0 is always present");
+            literal.push_str(&text[last..whole.start()]);
+            last = whole.end();
+
+            // Any suffix that isn't a unit stays in the literal text
+            let (token, rest) = if let Some(number) = captures.name("number") {
+                let suffix = captures.name("unit").map_or("", |unit| unit.as_str());
+                let (unit, rest) = match suffix.parse::<Unit>() {
+                    Ok(unit) => (Some(unit), ""),
+                    Err(_) => (None, suffix),
+                };
+                let token = number.as_str().parse().ok();
+                (
+                    token.map(|n| ComplexToken::Number(Value::new(n, unit))),
+                    rest,
+                )
+            } else {
+                // Words are only tokens if they're named colors
+                let token = crate::css::parse_color(whole.as_str()).ok();
+                (token.map(ComplexToken::Color), "")
+            };
+            match token {
+                Some(token) => {
+                    if !literal.is_empty() {
+                        tokens.push(ComplexToken::Literal(std::mem::take(&mut literal)));
+                    }
+                    tokens.push(token);
+                    literal.push_str(rest);
+                }
+                None => literal.push_str(whole.as_str()),
+            }
+        }
+        literal.push_str(&text[last..]);
+        if !literal.is_empty() {
+            tokens.push(ComplexToken::Literal(literal));
+        }
+
+        Self(tokens)
+    }
+
+    pub fn tokens(&self) -> &[ComplexToken] {
+        &self.0
+    }
+
+    /// Whether the values have the same template, so can be interpolated
+    pub fn is_compatible(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len()
+            && self.0.iter().zip(&other.0).all(|pair| match pair {
+                (ComplexToken::Number(a), ComplexToken::Number(b)) => align(a, b).is_some(),
+                (ComplexToken::Color(_), ComplexToken::Color(_)) => true,
+                (ComplexToken::Literal(a), ComplexToken::Literal(b)) => a == b,
+                _ => false,
+            })
+    }
+
+    /// The value at progress `t` between `self` and `to`, if they have the
+    /// same template
+    pub fn try_interpolate(&self, to: &Self, t: f32) -> Result<Self, ComplexValueError> {
+        self.try_interpolate_with(to, t, &Interpolation::default())
+    }
+
+    /// [`ComplexValue::try_interpolate`], with colors interpolated as set by
+    /// `options`
+    pub fn try_interpolate_with(
+        &self,
+        to: &Self,
+        t: f32,
+        options: &Interpolation,
+    ) -> Result<Self, ComplexValueError> {
+        if !self.is_compatible(to) {
+            return Err(ComplexValueError(self.to_string(), to.to_string()));
+        }
+        Ok(self.zip_with(
+            to,
+            |a, b| f32::interpolate(&a, &b, t),
+            |a, b| color::interpolate(a, b, t, options),
+        ))
+    }
+
+    /// Combine the numbers and colors in the same positions of two compatible
+    /// values
+    fn zip_with(
+        &self,
+        other: &Self,
+        number: impl Fn(f32, f32) -> f32,
+        color: impl Fn(&Color, &Color) -> Color,
+    ) -> Self {
+        Self(
+            self.0
+                .iter()
+                .zip(&other.0)
+                .map(|pair| match pair {
+                    (ComplexToken::Number(a), ComplexToken::Number(b)) => match align(a, b) {
+                        Some((a, b, unit)) => ComplexToken::Number(Value::new(number(a, b), unit)),
+                        None => ComplexToken::Number(a.clone()),
+                    },
+                    (ComplexToken::Color(a), ComplexToken::Color(b)) => {
+                        ComplexToken::Color(color(a, b))
+                    }
+                    (token, _) => token.clone(),
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Two numbers in the same unit: the second's (converting the first if it has
+/// a different one), or the first's if the second doesn't have one
+fn align(a: &Value<f32>, b: &Value<f32>) -> Option<(f32, f32, Option<Unit>)> {
+    match (a.unit(), b.unit()) {
+        (from, to) if from == to => Some((*a.value(), *b.value(), to)),
+        (None, unit) | (unit, None) => Some((*a.value(), *b.value(), unit)),
+        (_, Some(unit)) => Some((*a.convert(unit)?.value(), *b.value(), Some(unit))),
+    }
+}
+
+impl Display for ComplexValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for token in self.0.iter() {
+            match token {
+                ComplexToken::Number(number) => write!(f, "{}", number)?,
+                ComplexToken::Color(color) => color.fmt_css(None, f)?,
+                ComplexToken::Literal(literal) => f.write_str(literal)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl From<&str> for ComplexValue {
+    fn from(text: &str) -> Self {
+        ComplexValue::parse(text)
+    }
+}
+
+impl From<&str> for Value<ComplexValue> {
+    fn from(text: &str) -> Self {
+        Value::from(ComplexValue::parse(text))
+    }
+}
+
+impl Tweenable for ComplexValue {
+    fn interpolate(from: &Self, to: &Self, t: f32) -> Self {
+        Self::interpolate_with(from, to, t, &Interpolation::default())
+    }
+
+    /// Incompatible values can't be tweened, so switch from one to the other
+    /// halfway through (as CSS does for discrete values)
+    fn interpolate_with(from: &Self, to: &Self, t: f32, options: &Interpolation) -> Self {
+        from.try_interpolate_with(to, t, options)
+            .unwrap_or_else(|_| if t < 0.5 { from.clone() } else { to.clone() })
+    }
+
+    fn round(&self, round: u32) -> Self {
+        self.zip_with(self, |a, _| Tweenable::round(&a, round), |a, _| *a)
+    }
+
+    fn fmt_css(&self, _unit: Option<Unit>, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+
+    fn parse_css(text: &str) -> Option<Value<Self>> {
+        Some(Value::from(ComplexValue::parse(text)))
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        match self.is_compatible(other) {
+            true => self.zip_with(other, |a, b| a + b, |a, _| *a),
+            false => self.clone(),
+        }
+    }
+
+    fn subtract(&self, other: &Self) -> Self {
+        match self.is_compatible(other) {
+            true => self.zip_with(other, |a, b| a - b, |a, _| *a),
+            false => self.clone(),
+        }
+    }
+
+    fn multiply(&self, other: &Self) -> Self {
+        match self.is_compatible(other) {
+            true => self.zip_with(other, |a, b| a * b, |a, _| *a),
+            false => self.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn number(value: f32, unit: Option<Unit>) -> ComplexToken {
+        ComplexToken::Number(Value::new(value, unit))
+    }
+
+    fn literal(text: &str) -> ComplexToken {
+        ComplexToken::Literal(text.to_string())
+    }
+
+    #[test]
+    fn tokenises_values() {
+        assert_eq!(
+            ComplexValue::parse("0  0 10px  rgba(0, 0, 0, 0.5)").tokens(),
+            [
+                number(0.0, None),
+                literal(" "),
+                number(0.0, None),
+                literal(" "),
+                number(10.0, Some(Unit::Px)),
+                literal(" "),
+                ComplexToken::Color(Color::new(0, 0, 0, 0.5)),
+            ]
+        );
+        assert_eq!(
+            ComplexValue::parse("1.5em solid red").tokens(),
+            [
+                number(1.5, Some(Unit::Em)),
+                literal(" solid "),
+                ComplexToken::Color(Color::new(255, 0, 0, 1.0)),
+            ]
+        );
+        // Suffixes that aren't units are kept as text
+        assert_eq!(
+            ComplexValue::parse("blur(-2e1x)").tokens(),
+            [literal("blur("), number(-20.0, None), literal("x)")]
+        );
+        assert_eq!(ComplexValue::parse("none").tokens(), [literal("none")]);
+    }
+
+    #[test]
+    fn formats_values() {
+        let text = "0 0 10px rgba(0, 0, 0, 0.5)";
+        assert_eq!(ComplexValue::parse(text).to_string(), text);
+        assert_eq!(
+            ComplexValue::parse("#fff inset").to_string(),
+            "rgba(255, 255, 255, 1) inset"
+        );
+    }
+
+    #[test]
+    fn interpolates_values() {
+        let from = ComplexValue::parse("0 0 0 rgba(0, 0, 0, 0)");
+        let to = ComplexValue::parse("10px 20% 1in rgba(200, 100, 0, 1)");
+        assert_eq!(
+            from.try_interpolate(&to, 0.5).unwrap().to_string(),
+            "5px 10% 0.5in rgba(100, 50, 0, 0.5)"
+        );
+
+        // Units are converted if they can be
+        let from = ComplexValue::parse("translate(1in, 0)");
+        let to = ComplexValue::parse("translate(192px, 10px)");
+        assert_eq!(
+            from.try_interpolate(&to, 0.5).unwrap().to_string(),
+            "translate(144px, 5px)"
+        );
+    }
+
+    #[test]
+    fn doesnt_interpolate_different_templates() {
+        let from = ComplexValue::parse("1px solid red");
+        for to in [
+            "1px dashed red",
+            "1px solid",
+            "1% solid red",
+            "red solid 1px",
+        ] {
+            let to = ComplexValue::parse(to);
+            assert!(!from.is_compatible(&to), "{}", to);
+            assert!(from.try_interpolate(&to, 0.5).is_err());
+            assert_eq!(ComplexValue::interpolate(&from, &to, 0.4), from);
+            assert_eq!(ComplexValue::interpolate(&from, &to, 0.6), to);
+        }
+    }
+}
@@ -1,5 +1,15 @@
-use super::{Color, Property, PropertyValue, Value};
-use crate::builder::Builder;
+use std::rc::Rc;
+
+use super::{Color, ComplexValue, Property, PropertyKeyframe, PropertyValue, Value};
+use crate::{
+    builder::{
+        keyframe::KeyframeProperty,
+        property::{Parameters, TweenParameters},
+        Builder, ParameterValue,
+    },
+    engine::{Animatable, Animation, AnimationKind, TweenChain},
+    target::Target,
+};
 
 macro_rules! impl_css_properties {
     {
@@ -11,7 +21,7 @@ macro_rules! impl_css_properties {
         /// A subset of CSS properties supported for animation
         ///
         /// See https://developer.mozilla.org/en-US/docs/Web/CSS/Reference
-        #[derive(Debug, PartialEq)]
+        #[derive(Debug, Clone, PartialEq)]
         pub enum CssProperty {
             $(
                 $name(PropertyValue<$value_type>),
@@ -19,15 +29,76 @@ macro_rules! impl_css_properties {
         }
 
         impl CssProperty {
-            pub fn js_name(&self) -> &'static str {
+            /// Name of the property as used in css (kebab-case)
+            pub fn css_name(&self) -> String {
                 match self {
                     $(
-                        CssProperty::$name(_) => stringify!([<$name:camel>]),
+                        CssProperty::$name(_) => stringify!([<$name:snake>]).replace('_', "-"),
+                    )*
+                }
+            }
+
+            /// An empty keyframe chain for the same property
+            pub(crate) fn empty_keyframes(&self) -> Self {
+                match self {
+                    $(
+                        CssProperty::$name(_) => CssProperty::$name(PropertyValue::Keyframes(Vec::new())),
+                    )*
+                }
+            }
+
+            /// Append keyframes reaching `value` (a value of the same property)
+            /// to a keyframe chain, or hold the previous value if `None`
+            pub(crate) fn push_keyframe(
+                &mut self,
+                value: Option<&Self>,
+                params: &Parameters,
+                offset: Option<f32>,
+            ) {
+                match self {
+                    $(
+                        CssProperty::$name(PropertyValue::Keyframes(keyframes)) => {
+                            let value = match value {
+                                Some(CssProperty::$name(value)) => Some(value),
+                                _ => None,
+                            };
+                            keyframes.extend(PropertyKeyframe::from_value(value, params, offset));
+                        }
+                    )*
+                    _ => {}
+                }
+            }
+
+            pub(crate) fn animation(
+                &self,
+                animatable: Option<Rc<Animatable>>,
+                params: &TweenParameters,
+            ) -> Animation {
+                let kind = AnimationKind::Css(self.css_name());
+                match self {
+                    $(
+                        CssProperty::$name(value) => {
+                            // Only used if the computed value can't be read
+                            let initial = Value::from(<$value_type>::default());
+                            let tweens = TweenChain::new(value.clone(), params.clone(), initial, None);
+                            Animation::new(animatable, kind, tweens)
+                        }
                     )*
                 }
             }
         }
 
+        $(
+            impl crate::builder::property::Property<$value_type> {
+                /// The property with its own parameters, for use in an
+                /// [`AnimationKeyframe`](crate::builder::keyframe::AnimationKeyframe)
+                pub fn [<$name:snake>]<T: Into<crate::builder::property::Property<$value_type>>>(value: T) -> KeyframeProperty {
+                    let crate::builder::property::Property { value, params } = value.into();
+                    KeyframeProperty::new(Property::CssProperty(CssProperty::$name(value)), params)
+                }
+            }
+        )*
+
         impl Builder {
             $(
                 pub fn [<$name:snake>]<T: Into<crate::builder::property::Property<$value_type>>>(&mut self, value: T) -> &mut Self {
@@ -35,10 +106,27 @@ macro_rules! impl_css_properties {
                         value,
                         params
                     } = value.into();
-                    self.properties.push((
+                    self.properties.push(ParameterValue::Value((
                         Property::CssProperty(CssProperty::$name(value)),
                         params
-                    ));
+                    )));
+                    self
+                }
+
+                /// Compute the value for each target from the target, its index
+                /// and the number of targets (e.g. with [`stagger`](crate::stagger))
+                pub fn [<$name:snake _fn>]<F, T>(&mut self, f: F) -> &mut Self
+                where
+                    F: 'static + Fn(&Target, usize, usize) -> T,
+                    T: Into<crate::builder::property::Property<$value_type>>,
+                {
+                    self.properties.push(ParameterValue::Computed(Box::new(move |el, i, l| {
+                        let crate::builder::property::Property {
+                            value,
+                            params
+                        } = f(el, i, l).into();
+                        (Property::CssProperty(CssProperty::$name(value)), params)
+                    })));
                     self
                 }
             )*
@@ -46,20 +134,6 @@ macro_rules! impl_css_properties {
     }};
 }
 
-enum CssValue {
-    Angle(Angle)
-}
-
-/// Clockwise Angle
-enum Angle {
-    Degrees(f32),
-    Gradians(f32),
-    Radians(f32),
-    Turns(f32),
-}
-
-
-
 impl_css_properties! {
     AccentColor => Color,
     BackgroundColor => Color,
@@ -102,6 +176,14 @@ impl_css_properties! {
 
     Bottom => f32,
 
-}
+    BoxShadow => ComplexValue,
+    Filter => ComplexValue,
+    TextShadow => ComplexValue,
 
-// Left(PropertyValue<i32>),
+    Height => f32,
+    Left => f32,
+    Opacity => f32,
+    Right => f32,
+    Top => f32,
+    Width => f32,
+}
@@ -1,36 +1,44 @@
-use super::{value::Unit, Property as Prop, PropertyParameters, PropertyValue, Value};
-use crate::builder::{property::Property, Builder};
+use std::rc::Rc;
+
+use super::{
+    matrix::{IDENTITY_2D, IDENTITY_3D},
+    value::Unit,
+    Angle, Orientation, Property as Prop, PropertyKeyframe, PropertyValue, Value,
+};
+use crate::{
+    builder::{
+        keyframe::KeyframeProperty,
+        property::{Parameters, Property, TweenParameters},
+        Builder, ParameterValue,
+    },
+    engine::{Animatable, Animation, AnimationKind, TweenChain},
+    target::Target,
+};
 
 macro_rules! impl_css_transforms {
     {
         $(
-            $name:ident => $value_type:ty {
-                default_unit: $units:expr
+            $name:ident as $method:ident => $value_type:ty {
+                css_name: $css_name:literal,
+                default_unit: $units:expr,
+                initial: $initial:expr
             },
         )*
     } => {paste::paste!{
 
-        #[derive(Debug, PartialEq)]
+        #[derive(Debug, Clone, PartialEq)]
         pub enum CssTransform {
             $(
                 $name(PropertyValue<$value_type>),
             )*
         }
 
-        // TODO: fields with stringify!([<$name:snake>])
         impl CssTransform {
-            // fn css_name(&self) -> &'static str {
-            //     match self {
-            //         $(
-            //             CssTransform::$name(_) => $css_name,
-            //         )*
-            //     }
-            // }
-
-            pub fn js_name(&self) -> &'static str {
+            /// The name of the transform function, e.g. `translateX`
+            pub fn css_name(&self) -> &'static str {
                 match self {
                     $(
-                        CssTransform::$name(_) => stringify!([<$name:camel>]),
+                        CssTransform::$name(_) => $css_name,
                     )*
                 }
             }
@@ -42,19 +50,95 @@ macro_rules! impl_css_transforms {
                     )*
                 }
             }
+
+            /// An empty keyframe chain for the same property
+            pub(crate) fn empty_keyframes(&self) -> Self {
+                match self {
+                    $(
+                        CssTransform::$name(_) => CssTransform::$name(PropertyValue::Keyframes(Vec::new())),
+                    )*
+                }
+            }
+
+            /// Append keyframes reaching `value` (a value of the same property)
+            /// to a keyframe chain, or hold the previous value if `None`
+            pub(crate) fn push_keyframe(
+                &mut self,
+                value: Option<&Self>,
+                params: &Parameters,
+                offset: Option<f32>,
+            ) {
+                match self {
+                    $(
+                        CssTransform::$name(PropertyValue::Keyframes(keyframes)) => {
+                            let value = match value {
+                                Some(CssTransform::$name(value)) => Some(value),
+                                _ => None,
+                            };
+                            keyframes.extend(PropertyKeyframe::from_value(value, params, offset));
+                        }
+                    )*
+                    _ => {}
+                }
+            }
+
+            pub(crate) fn animation(
+                &self,
+                animatable: Option<Rc<Animatable>>,
+                params: &TweenParameters,
+            ) -> Animation {
+                let kind = AnimationKind::Transform(self.css_name());
+                let default_unit = self.default_units();
+                match self {
+                    $(
+                        CssTransform::$name(value) => {
+                            // Only used if the element doesn't have this transform
+                            let initial = Value::from($initial);
+                            let tweens =
+                                TweenChain::new(value.clone(), params.clone(), initial, default_unit);
+                            Animation::new(animatable, kind, tweens)
+                        }
+                    )*
+                }
+            }
         }
 
+        $(
+            impl Property<$value_type> {
+                /// The property with its own parameters, for use in an
+                /// [`AnimationKeyframe`](crate::builder::keyframe::AnimationKeyframe)
+                pub fn $method<T: Into<Property<$value_type>>>(value: T) -> KeyframeProperty {
+                    let Property { value, params } = value.into();
+                    KeyframeProperty::new(Prop::CssTransform(CssTransform::$name(value)), params)
+                }
+            }
+        )*
+
         impl Builder {
             $(
-                pub fn [<$name:snake>]<T: Into<Property<$value_type>>>(&mut self, value: T) -> &mut Self {
+                pub fn $method<T: Into<Property<$value_type>>>(&mut self, value: T) -> &mut Self {
                     let crate::builder::property::Property {
                         value,
                         params
                     } = value.into();
-                    self.properties.push((
+                    self.properties.push(ParameterValue::Value((
                         Prop::CssTransform(CssTransform::$name(value)),
                         params
-                    ));
+                    )));
+                    self
+                }
+
+                /// Compute the value for each target from the target, its index
+                /// and the number of targets (e.g. with [`stagger`](crate::stagger))
+                pub fn [<$method _fn>]<F, T>(&mut self, f: F) -> &mut Self
+                where
+                    F: 'static + Fn(&Target, usize, usize) -> T,
+                    T: Into<Property<$value_type>>,
+                {
+                    self.properties.push(ParameterValue::Computed(Box::new(move |el, i, l| {
+                        let Property { value, params } = f(el, i, l).into();
+                        (Prop::CssTransform(CssTransform::$name(value)), params)
+                    })));
                     self
                 }
             )*
@@ -64,26 +148,55 @@ macro_rules! impl_css_transforms {
 
 use Unit::*;
 impl_css_transforms! {
-    TranslateX => i32 { default_unit: Some(Px) },
-    TranslateY => i32 { default_unit: Some(Px) },
-    TranslateZ => i32 { default_unit: Some(Px) },
+    TranslateX as translate_x => i32 { css_name: "translateX", default_unit: Some(Px), initial: 0 },
+    TranslateY as translate_y => i32 { css_name: "translateY", default_unit: Some(Px), initial: 0 },
+    TranslateZ as translate_z => i32 { css_name: "translateZ", default_unit: Some(Px), initial: 0 },
 
-    Rotate => i32 { default_unit: Some(Deg) },
-    RotateX => i32 { default_unit:  Some(Deg) },
-    RotateY => i32 { default_unit:  Some(Deg) },
-    RotateZ => i32 { default_unit:  Some(Deg) },
+    Rotate as rotate => Angle { css_name: "rotate", default_unit: None, initial: Angle::Degrees(0.0) },
+    RotateX as rotate_x => Angle { css_name: "rotateX", default_unit: None, initial: Angle::Degrees(0.0) },
+    RotateY as rotate_y => Angle { css_name: "rotateY", default_unit: None, initial: Angle::Degrees(0.0) },
+    RotateZ as rotate_z => Angle { css_name: "rotateZ", default_unit: None, initial: Angle::Degrees(0.0) },
+    Rotate3D as rotate_3d => Orientation { css_name: "rotate3d", default_unit: None, initial: Orientation::default() },
 
-    Scale => f32 { default_unit: Some(Dimensionless) },
-    ScaleX => f32 { default_unit: Some(Dimensionless) },
-    ScaleY => f32 { default_unit: Some(Dimensionless) },
-    ScaleZ => f32 { default_unit: Some(Dimensionless) },
+    Scale as scale => f32 { css_name: "scale", default_unit: Some(Dimensionless), initial: 1.0 },
+    ScaleX as scale_x => f32 { css_name: "scaleX", default_unit: Some(Dimensionless), initial: 1.0 },
+    ScaleY as scale_y => f32 { css_name: "scaleY", default_unit: Some(Dimensionless), initial: 1.0 },
+    ScaleZ as scale_z => f32 { css_name: "scaleZ", default_unit: Some(Dimensionless), initial: 1.0 },
 
-    Skew => i32 { default_unit: Some(Deg) },
-    SkewX => i32 { default_unit: Some(Deg) },
-    SkewY => i32 { default_unit: Some(Deg) },
+    Skew as skew => Angle { css_name: "skew", default_unit: None, initial: Angle::Degrees(0.0) },
+    SkewX as skew_x => Angle { css_name: "skewX", default_unit: None, initial: Angle::Degrees(0.0) },
+    SkewY as skew_y => Angle { css_name: "skewY", default_unit: None, initial: Angle::Degrees(0.0) },
+
+    Perspective as perspective => i32 { css_name: "perspective", default_unit: Some(Px), initial: 0 },
+
+    Matrix as matrix => [f32; 6] { css_name: "matrix", default_unit: None, initial: IDENTITY_2D },
+    Matrix3D as matrix_3d => [f32; 16] { css_name: "matrix3d", default_unit: None, initial: IDENTITY_3D },
+}
 
-    Perspective => i32 { default_unit: Some(Px) },
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    Matrix => i32 { default_unit: None },
-    Matrix3D => i32 { default_unit: None },
+    #[test]
+    fn uses_css_function_names() {
+        let name = |transform: CssTransform| transform.css_name();
+        assert_eq!(
+            name(CssTransform::TranslateX(PropertyValue::from(0))),
+            "translateX"
+        );
+        assert_eq!(
+            name(CssTransform::ScaleZ(PropertyValue::from(1.0))),
+            "scaleZ"
+        );
+        assert_eq!(
+            name(CssTransform::Rotate3D(PropertyValue::from(
+                Orientation::default()
+            ))),
+            "rotate3d"
+        );
+        assert_eq!(
+            name(CssTransform::Matrix3D(PropertyValue::from(IDENTITY_3D))),
+            "matrix3d"
+        );
+    }
 }
@@ -1 +1,2 @@
+#[allow(dead_code)] // TODO: animate dom attributes
 pub enum DomAttribute {}
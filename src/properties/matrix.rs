@@ -0,0 +1,788 @@
+//! Interpolating `matrix()` and `matrix3d()` transforms by decomposing them
+//! into translation, scale, skew and rotation, as in the CSS Transforms spec
+//!
+//! See https://www.w3.org/TR/css-transforms-1/#interpolation-of-2d-matrices
+//! and https://www.w3.org/TR/css-transforms-2/#interpolation-of-3d-matrices
+
+use super::{Tweenable, Unit, Value};
+
+/// The identity `matrix(a, b, c, d, e, f)`
+pub(crate) const IDENTITY_2D: [f32; 6] = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+
+/// The identity `matrix3d(...)`, in column-major order (as in css)
+pub(crate) const IDENTITY_3D: [f32; 16] = [
+    1.0, 0.0, 0.0, 0.0, //
+    0.0, 1.0, 0.0, 0.0, //
+    0.0, 0.0, 1.0, 0.0, //
+    0.0, 0.0, 0.0, 1.0, //
+];
+
+/// A 4x4 matrix, indexed `[column][row]`
+type Matrix4 = [[f32; 4]; 4];
+
+fn lerp(from: f32, to: f32, t: f32) -> f32 {
+    f32::interpolate(&from, &to, t)
+}
+
+fn lerp_all<const N: usize>(from: &[f32; N], to: &[f32; N], t: f32) -> [f32; N] {
+    let mut result = [0.0; N];
+    for i in 0..N {
+        result[i] = lerp(from[i], to[i], t);
+    }
+    result
+}
+
+/// Write a matrix's values as the arguments of `matrix()` or `matrix3d()`
+fn fmt_matrix(values: &[f32], f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    for (i, value) in values.iter().enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{}", value)?;
+    }
+    Ok(())
+}
+
+/// Read the arguments of `matrix()` or `matrix3d()`
+fn parse_matrix<const N: usize>(text: &str) -> Option<[f32; N]> {
+    let mut result = [0.0; N];
+    let mut values = text.split(',');
+    for value in result.iter_mut() {
+        *value = values.next()?.trim().parse().ok()?;
+    }
+    match values.next() {
+        Some(_) => None,
+        None => Some(result),
+    }
+}
+
+/// A 2D matrix, decomposed into transforms that interpolate naturally
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Decomposed2D {
+    translate: [f32; 2],
+    scale: [f32; 2],
+    /// Rotation, in degrees
+    angle: f32,
+    /// What's left (the skew), as `[m11, m12, m21, m22]`
+    skew: [f32; 4],
+}
+
+impl Decomposed2D {
+    fn decompose(matrix: &[f32; 6]) -> Self {
+        let [a, b, c, d, e, f] = *matrix;
+        let (mut row0x, mut row0y, mut row1x, mut row1y) = (a, b, c, d);
+
+        let mut scale = [a.hypot(b), c.hypot(d)];
+
+        // If the determinant is negative, one axis was flipped. Flip the axis
+        // with the minimum unit vector dot product
+        if a * d - b * c < 0.0 {
+            if a < d {
+                scale[0] = -scale[0];
+            } else {
+                scale[1] = -scale[1];
+            }
+        }
+
+        // Renormalize to remove the scale
+        if scale[0] != 0.0 {
+            row0x /= scale[0];
+            row0y /= scale[0];
+        }
+        if scale[1] != 0.0 {
+            row1x /= scale[1];
+            row1y /= scale[1];
+        }
+
+        // Remove the rotation
+        let angle = row0y.atan2(row0x);
+        if angle != 0.0 {
+            let (sn, cs) = (-row0y, row0x);
+            let (m11, m12, m21, m22) = (row0x, row0y, row1x, row1y);
+            row0x = cs * m11 + sn * m21;
+            row0y = cs * m12 + sn * m22;
+            row1x = -sn * m11 + cs * m21;
+            row1y = -sn * m12 + cs * m22;
+        }
+
+        Self {
+            translate: [e, f],
+            scale,
+            angle: angle.to_degrees(),
+            skew: [row0x, row0y, row1x, row1y],
+        }
+    }
+
+    fn recompose(&self) -> [f32; 6] {
+        let [m11, m12, m21, m22] = self.skew;
+        let (sin, cos) = self.angle.to_radians().sin_cos();
+        let [sx, sy] = self.scale;
+        let [tx, ty] = self.translate;
+        [
+            (m11 * cos + m21 * sin) * sx,
+            (m12 * cos + m22 * sin) * sx,
+            (m21 * cos - m11 * sin) * sy,
+            (m22 * cos - m12 * sin) * sy,
+            tx,
+            ty,
+        ]
+    }
+
+    fn interpolate(from: &Self, to: &Self, t: f32) -> Self {
+        let (mut from, mut to) = (*from, *to);
+
+        // If the x axis of one is flipped, and the y axis of the other,
+        // convert to an unflipped rotation
+        if (from.scale[0] < 0.0 && to.scale[1] < 0.0) || (from.scale[1] < 0.0 && to.scale[0] < 0.0)
+        {
+            from.scale = [-from.scale[0], -from.scale[1]];
+            from.angle += if from.angle < 0.0 { 180.0 } else { -180.0 };
+        }
+
+        // Don't rotate the long way around
+        if from.angle == 0.0 {
+            from.angle = 360.0;
+        }
+        if to.angle == 0.0 {
+            to.angle = 360.0;
+        }
+        if (from.angle - to.angle).abs() > 180.0 {
+            if from.angle > to.angle {
+                from.angle -= 360.0;
+            } else {
+                to.angle -= 360.0;
+            }
+        }
+
+        Self {
+            translate: lerp_all(&from.translate, &to.translate, t),
+            scale: lerp_all(&from.scale, &to.scale, t),
+            angle: lerp(from.angle, to.angle, t),
+            skew: lerp_all(&from.skew, &to.skew, t),
+        }
+    }
+}
+
+/// A 3D matrix, decomposed into transforms that interpolate naturally
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Decomposed3D {
+    translate: [f32; 3],
+    scale: [f32; 3],
+    /// XY, XZ and YZ shears
+    skew: [f32; 3],
+    perspective: [f32; 4],
+    /// Rotation, as a quaternion `[x, y, z, w]`
+    quaternion: [f32; 4],
+}
+
+impl Decomposed3D {
+    /// Decompose a matrix, if it can be (it isn't singular)
+    fn decompose(matrix: &[f32; 16]) -> Option<Self> {
+        let mut matrix = to_matrix4(matrix);
+
+        // Normalize the matrix
+        if matrix[3][3] == 0.0 {
+            return None;
+        }
+        let w = matrix[3][3];
+        matrix.iter_mut().flatten().for_each(|value| *value /= w);
+
+        // Used to solve for perspective, but also an easy way to test for
+        // singularity of the upper 3x3 component
+        let mut perspective_matrix = matrix;
+        for column in perspective_matrix.iter_mut().take(3) {
+            column[3] = 0.0;
+        }
+        perspective_matrix[3][3] = 1.0;
+        let inverse = invert(&perspective_matrix)?;
+
+        // Isolate perspective
+        let perspective = if matrix[0][3] != 0.0 || matrix[1][3] != 0.0 || matrix[2][3] != 0.0 {
+            let rhs = [matrix[0][3], matrix[1][3], matrix[2][3], matrix[3][3]];
+            // rhs * inverse, treating rhs as a row vector
+            let mut perspective = [0.0; 4];
+            for (i, value) in perspective.iter_mut().enumerate() {
+                *value = (0..4).map(|j| rhs[j] * inverse[i][j]).sum();
+            }
+            perspective
+        } else {
+            [0.0, 0.0, 0.0, 1.0]
+        };
+
+        let translate = [matrix[3][0], matrix[3][1], matrix[3][2]];
+
+        // Scale and shear
+        let mut row = [[0.0; 3]; 3];
+        for (i, row) in row.iter_mut().enumerate() {
+            row.copy_from_slice(&matrix[i][..3]);
+        }
+        let mut scale = [0.0; 3];
+        let mut skew = [0.0; 3];
+
+        // X scale, and normalize the first row
+        scale[0] = length(&row[0]);
+        row[0] = normalize(&row[0]);
+
+        // XY shear, and make the 2nd row orthogonal to the 1st
+        skew[0] = dot(&row[0], &row[1]);
+        row[1] = combine(&row[1], &row[0], 1.0, -skew[0]);
+
+        // Y scale, and normalize the 2nd row
+        scale[1] = length(&row[1]);
+        row[1] = normalize(&row[1]);
+        skew[0] /= scale[1];
+
+        // XZ and YZ shears, and orthogonalize the 3rd row
+        skew[1] = dot(&row[0], &row[2]);
+        row[2] = combine(&row[2], &row[0], 1.0, -skew[1]);
+        skew[2] = dot(&row[1], &row[2]);
+        row[2] = combine(&row[2], &row[1], 1.0, -skew[2]);
+
+        // Z scale, and normalize the 3rd row
+        scale[2] = length(&row[2]);
+        row[2] = normalize(&row[2]);
+        skew[1] /= scale[2];
+        skew[2] /= scale[2];
+
+        // The rows are now orthonormal. If the determinant is -1 the
+        // coordinate system was flipped, so negate the rows and scale
+        if dot(&row[0], &cross(&row[1], &row[2])) < 0.0 {
+            for i in 0..3 {
+                scale[i] = -scale[i];
+                row[i] = row[i].map(|value| -value);
+            }
+        }
+
+        Some(Self {
+            translate,
+            scale,
+            skew,
+            perspective,
+            quaternion: quaternion(&row),
+        })
+    }
+
+    fn recompose(&self) -> [f32; 16] {
+        let mut matrix = to_matrix4(&IDENTITY_3D);
+
+        // Perspective
+        for (i, column) in matrix.iter_mut().enumerate() {
+            column[3] = self.perspective[i];
+        }
+
+        // Translation
+        let columns = matrix;
+        for (i, value) in matrix[3].iter_mut().enumerate() {
+            *value += (0..3)
+                .map(|j| self.translate[j] * columns[j][i])
+                .sum::<f32>();
+        }
+
+        // Rotation
+        let [x, y, z, w] = self.quaternion;
+        let mut rotation = to_matrix4(&IDENTITY_3D);
+        rotation[0][0] = 1.0 - 2.0 * (y * y + z * z);
+        rotation[0][1] = 2.0 * (x * y + z * w);
+        rotation[0][2] = 2.0 * (x * z - y * w);
+        rotation[1][0] = 2.0 * (x * y - z * w);
+        rotation[1][1] = 1.0 - 2.0 * (x * x + z * z);
+        rotation[1][2] = 2.0 * (y * z + x * w);
+        rotation[2][0] = 2.0 * (x * z + y * w);
+        rotation[2][1] = 2.0 * (y * z - x * w);
+        rotation[2][2] = 1.0 - 2.0 * (x * x + y * y);
+        matrix = multiply(&matrix, &rotation);
+
+        // Skew
+        let mut temp = to_matrix4(&IDENTITY_3D);
+        if self.skew[2] != 0.0 {
+            temp[2][1] = self.skew[2];
+            matrix = multiply(&matrix, &temp);
+        }
+        if self.skew[1] != 0.0 {
+            temp[2][1] = 0.0;
+            temp[2][0] = self.skew[1];
+            matrix = multiply(&matrix, &temp);
+        }
+        if self.skew[0] != 0.0 {
+            temp[2][0] = 0.0;
+            temp[1][0] = self.skew[0];
+            matrix = multiply(&matrix, &temp);
+        }
+
+        // Scale
+        for (i, column) in matrix.iter_mut().take(3).enumerate() {
+            column.iter_mut().for_each(|value| *value *= self.scale[i]);
+        }
+
+        from_matrix4(&matrix)
+    }
+
+    fn interpolate(from: &Self, to: &Self, t: f32) -> Self {
+        Self {
+            translate: lerp_all(&from.translate, &to.translate, t),
+            scale: lerp_all(&from.scale, &to.scale, t),
+            skew: lerp_all(&from.skew, &to.skew, t),
+            perspective: lerp_all(&from.perspective, &to.perspective, t),
+            quaternion: slerp(&from.quaternion, &to.quaternion, t),
+        }
+    }
+}
+
+/// The quaternion `[x, y, z, w]` of a rotation matrix, given as its columns.
+///
+/// Rather than the spec's method (which takes the signs of `x`, `y` and `z`
+/// from the matrix, so loses them for rotations of close to 180°, like those
+/// of flipped matrices), this starts from the largest component
+fn quaternion(columns: &[[f32; 3]; 3]) -> [f32; 4] {
+    let m = |row: usize, column: usize| columns[column][row];
+    let trace = m(0, 0) + m(1, 1) + m(2, 2);
+
+    if trace > 0.0 {
+        let s = 2.0 * (1.0 + trace).sqrt();
+        [
+            (m(2, 1) - m(1, 2)) / s,
+            (m(0, 2) - m(2, 0)) / s,
+            (m(1, 0) - m(0, 1)) / s,
+            0.25 * s,
+        ]
+    } else if m(0, 0) > m(1, 1) && m(0, 0) > m(2, 2) {
+        let s = 2.0 * (1.0 + m(0, 0) - m(1, 1) - m(2, 2)).sqrt();
+        [
+            0.25 * s,
+            (m(0, 1) + m(1, 0)) / s,
+            (m(0, 2) + m(2, 0)) / s,
+            (m(2, 1) - m(1, 2)) / s,
+        ]
+    } else if m(1, 1) > m(2, 2) {
+        let s = 2.0 * (1.0 + m(1, 1) - m(0, 0) - m(2, 2)).sqrt();
+        [
+            (m(0, 1) + m(1, 0)) / s,
+            0.25 * s,
+            (m(1, 2) + m(2, 1)) / s,
+            (m(0, 2) - m(2, 0)) / s,
+        ]
+    } else {
+        let s = 2.0 * (1.0 + m(2, 2) - m(0, 0) - m(1, 1)).sqrt();
+        [
+            (m(0, 2) + m(2, 0)) / s,
+            (m(1, 2) + m(2, 1)) / s,
+            0.25 * s,
+            (m(1, 0) - m(0, 1)) / s,
+        ]
+    }
+}
+
+/// Spherical linear interpolation between two rotations (the shortest way
+/// around)
+pub(crate) fn slerp(from: &[f32; 4], to: &[f32; 4], t: f32) -> [f32; 4] {
+    let mut to = *to;
+    let mut product: f32 = (0..4).map(|i| from[i] * to[i]).sum();
+    // `q` and `-q` are the same rotation, so go the short way (keeping the
+    // direction given for half turns, which are the same length either way)
+    if product < -1e-6 {
+        to = to.map(|value| -value);
+        product = -product;
+    }
+    let product = product.min(1.0);
+    if product >= 1.0 - f32::EPSILON {
+        return *from;
+    }
+
+    let theta = product.acos();
+    let w = (t * theta).sin() / (1.0 - product * product).sqrt();
+    let mut result = [0.0; 4];
+    for i in 0..4 {
+        result[i] = from[i] * ((t * theta).cos() - product * w) + to[i] * w;
+    }
+    result
+}
+
+fn to_matrix4(values: &[f32; 16]) -> Matrix4 {
+    let mut matrix = [[0.0; 4]; 4];
+    for (i, column) in matrix.iter_mut().enumerate() {
+        column.copy_from_slice(&values[i * 4..i * 4 + 4]);
+    }
+    matrix
+}
+
+fn from_matrix4(matrix: &Matrix4) -> [f32; 16] {
+    let mut values = [0.0; 16];
+    for (i, column) in matrix.iter().enumerate() {
+        values[i * 4..i * 4 + 4].copy_from_slice(column);
+    }
+    values
+}
+
+/// `a * b`
+fn multiply(a: &Matrix4, b: &Matrix4) -> Matrix4 {
+    let mut result = [[0.0; 4]; 4];
+    for (i, column) in result.iter_mut().enumerate() {
+        for (j, value) in column.iter_mut().enumerate() {
+            *value = (0..4).map(|k| a[k][j] * b[i][k]).sum();
+        }
+    }
+    result
+}
+
+/// The inverse of a matrix, by Gauss-Jordan elimination, if it isn't singular
+fn invert(matrix: &Matrix4) -> Option<Matrix4> {
+    // Work in rows, alongside the identity
+    let mut a = [[0.0; 8]; 4];
+    for (row, values) in a.iter_mut().enumerate() {
+        for column in 0..4 {
+            values[column] = matrix[column][row];
+        }
+        values[4 + row] = 1.0;
+    }
+
+    for column in 0..4 {
+        let pivot = (column..4).max_by(|&i, &j| {
+            a[i][column]
+                .abs()
+                .partial_cmp(&a[j][column].abs())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })?;
+        if a[pivot][column].abs() < f32::EPSILON {
+            return None;
+        }
+        a.swap(column, pivot);
+
+        let divisor = a[column][column];
+        a[column].iter_mut().for_each(|value| *value /= divisor);
+        let pivot_row = a[column];
+        for (row, values) in a.iter_mut().enumerate() {
+            if row != column {
+                let factor = values[column];
+                for (value, pivot) in values.iter_mut().zip(pivot_row.iter()) {
+                    *value -= factor * pivot;
+                }
+            }
+        }
+    }
+
+    let mut inverse = [[0.0; 4]; 4];
+    for (row, values) in a.iter().enumerate() {
+        for column in 0..4 {
+            inverse[column][row] = values[4 + column];
+        }
+    }
+    Some(inverse)
+}
+
+fn dot(a: &[f32; 3], b: &[f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: &[f32; 3], b: &[f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn length(a: &[f32; 3]) -> f32 {
+    dot(a, a).sqrt()
+}
+
+fn normalize(a: &[f32; 3]) -> [f32; 3] {
+    let length = length(a);
+    if length == 0.0 {
+        *a
+    } else {
+        a.map(|value| value / length)
+    }
+}
+
+/// `a * a_scale + b * b_scale`
+fn combine(a: &[f32; 3], b: &[f32; 3], a_scale: f32, b_scale: f32) -> [f32; 3] {
+    [
+        a[0] * a_scale + b[0] * b_scale,
+        a[1] * a_scale + b[1] * b_scale,
+        a[2] * a_scale + b[2] * b_scale,
+    ]
+}
+
+/// `matrix(a, b, c, d, e, f)`
+impl Tweenable for [f32; 6] {
+    fn interpolate(from: &Self, to: &Self, t: f32) -> Self {
+        let (from, to) = (Decomposed2D::decompose(from), Decomposed2D::decompose(to));
+        Decomposed2D::interpolate(&from, &to, t).recompose()
+    }
+
+    fn round(&self, round: u32) -> Self {
+        self.map(|value| Tweenable::round(&value, round))
+    }
+
+    fn fmt_css(&self, _unit: Option<Unit>, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt_matrix(self, f)
+    }
+
+    fn parse_css(text: &str) -> Option<Value<Self>> {
+        parse_matrix(text).map(Value::from)
+    }
+}
+
+/// `matrix3d(...)`, in column-major order
+impl Tweenable for [f32; 16] {
+    /// Matrices that can't be decomposed switch from one to the other halfway
+    /// through, as in css
+    fn interpolate(from: &Self, to: &Self, t: f32) -> Self {
+        match (Decomposed3D::decompose(from), Decomposed3D::decompose(to)) {
+            (Some(from), Some(to)) => Decomposed3D::interpolate(&from, &to, t).recompose(),
+            _ if t < 0.5 => *from,
+            _ => *to,
+        }
+    }
+
+    fn round(&self, round: u32) -> Self {
+        self.map(|value| Tweenable::round(&value, round))
+    }
+
+    fn fmt_css(&self, _unit: Option<Unit>, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt_matrix(self, f)
+    }
+
+    fn parse_css(text: &str) -> Option<Value<Self>> {
+        parse_matrix(text).map(Value::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: &[f32], expected: &[f32]) {
+        let close = actual
+            .iter()
+            .zip(expected)
+            .all(|(a, b)| (a - b).abs() < 1e-4);
+        assert!(close, "{:?} != {:?}", actual, expected);
+    }
+
+    /// `translate(tx, ty) rotate(angle) scale(sx, sy) skewX(skew)`
+    fn matrix_2d(translate: [f32; 2], angle: f32, scale: [f32; 2], skew: f32) -> [f32; 6] {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        let tan = skew.to_radians().tan();
+        let [sx, sy] = scale;
+        let [tx, ty] = translate;
+        [
+            sx * cos,
+            sx * sin,
+            sx * cos * tan - sy * sin,
+            sx * sin * tan + sy * cos,
+            tx,
+            ty,
+        ]
+    }
+
+    /// `matrices[0] matrices[1] ...`
+    fn compose_3d(matrices: &[[f32; 16]]) -> [f32; 16] {
+        let product = matrices
+            .iter()
+            .fold(to_matrix4(&IDENTITY_3D), |product, matrix| {
+                multiply(&product, &to_matrix4(matrix))
+            });
+        from_matrix4(&product)
+    }
+
+    fn translate_3d(x: f32, y: f32, z: f32) -> [f32; 16] {
+        let mut matrix = IDENTITY_3D;
+        matrix[12..15].copy_from_slice(&[x, y, z]);
+        matrix
+    }
+
+    fn scale_3d(x: f32, y: f32, z: f32) -> [f32; 16] {
+        let mut matrix = IDENTITY_3D;
+        matrix[0] = x;
+        matrix[5] = y;
+        matrix[10] = z;
+        matrix
+    }
+
+    fn rotate_z(angle: f32) -> [f32; 16] {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        let mut matrix = IDENTITY_3D;
+        matrix[..2].copy_from_slice(&[cos, sin]);
+        matrix[4..6].copy_from_slice(&[-sin, cos]);
+        matrix
+    }
+
+    fn rotate_x(angle: f32) -> [f32; 16] {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        let mut matrix = IDENTITY_3D;
+        matrix[5..7].copy_from_slice(&[cos, sin]);
+        matrix[9..11].copy_from_slice(&[-sin, cos]);
+        matrix
+    }
+
+    fn skew_x(angle: f32) -> [f32; 16] {
+        let mut matrix = IDENTITY_3D;
+        matrix[4] = angle.to_radians().tan();
+        matrix
+    }
+
+    /// The quaternion of a rotation about the z axis
+    fn z_quaternion(angle: f32) -> [f32; 4] {
+        let (sin, cos) = (angle / 2.0).to_radians().sin_cos();
+        [0.0, 0.0, sin, cos]
+    }
+
+    #[test]
+    fn decomposes_2d_matrices() {
+        let identity = Decomposed2D::decompose(&IDENTITY_2D);
+        assert_eq!(identity.translate, [0.0, 0.0]);
+        assert_eq!(identity.scale, [1.0, 1.0]);
+        assert_eq!(identity.angle, 0.0);
+        assert_eq!(identity.skew, [1.0, 0.0, 0.0, 1.0]);
+
+        let decomposed = Decomposed2D::decompose(&matrix_2d([10.0, -5.0], 30.0, [2.0, 3.0], 0.0));
+        assert_close(&decomposed.translate, &[10.0, -5.0]);
+        assert_close(&decomposed.scale, &[2.0, 3.0]);
+        assert_close(&[decomposed.angle], &[30.0]);
+        assert_close(&decomposed.skew, &[1.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn recomposes_2d_matrices() {
+        for matrix in [
+            IDENTITY_2D,
+            matrix_2d([10.0, -5.0], 30.0, [2.0, 3.0], 0.0),
+            matrix_2d([0.0, 0.0], 0.0, [1.0, 1.0], 20.0),
+            matrix_2d([1.0, 2.0], -120.0, [0.5, 4.0], -35.0),
+            // Flipped
+            matrix_2d([0.0, 0.0], 45.0, [-1.0, 2.0], 0.0),
+        ] {
+            assert_close(&Decomposed2D::decompose(&matrix).recompose(), &matrix);
+        }
+    }
+
+    #[test]
+    fn interpolates_2d_matrices() {
+        let from = matrix_2d([0.0, 0.0], 0.0, [1.0, 1.0], 0.0);
+        let to = matrix_2d([100.0, 50.0], 90.0, [3.0, 2.0], 0.0);
+        assert_close(&Tweenable::interpolate(&from, &to, 0.0), &from);
+        assert_close(&Tweenable::interpolate(&from, &to, 1.0), &to);
+        assert_close(
+            &Tweenable::interpolate(&from, &to, 0.5),
+            &matrix_2d([50.0, 25.0], 45.0, [2.0, 1.5], 0.0),
+        );
+
+        // The short way around
+        let from = matrix_2d([0.0, 0.0], 170.0, [1.0, 1.0], 0.0);
+        let to = matrix_2d([0.0, 0.0], -170.0, [1.0, 1.0], 0.0);
+        assert_close(
+            &Tweenable::interpolate(&from, &to, 0.5),
+            &matrix_2d([0.0, 0.0], 180.0, [1.0, 1.0], 0.0),
+        );
+    }
+
+    #[test]
+    fn decomposes_3d_matrices() {
+        let identity = Decomposed3D::decompose(&IDENTITY_3D).unwrap();
+        assert_eq!(identity.translate, [0.0; 3]);
+        assert_eq!(identity.scale, [1.0; 3]);
+        assert_eq!(identity.skew, [0.0; 3]);
+        assert_eq!(identity.perspective, [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(identity.quaternion, [0.0, 0.0, 0.0, 1.0]);
+
+        let matrix = compose_3d(&[
+            translate_3d(10.0, 20.0, -30.0),
+            rotate_z(60.0),
+            scale_3d(2.0, 3.0, 4.0),
+        ]);
+        let decomposed = Decomposed3D::decompose(&matrix).unwrap();
+        assert_close(&decomposed.translate, &[10.0, 20.0, -30.0]);
+        assert_close(&decomposed.scale, &[2.0, 3.0, 4.0]);
+        assert_close(&decomposed.skew, &[0.0; 3]);
+        assert_close(&decomposed.quaternion, &z_quaternion(60.0));
+
+        let skewed = Decomposed3D::decompose(&skew_x(45.0)).unwrap();
+        assert_close(&skewed.skew, &[1.0, 0.0, 0.0]);
+        assert_close(&skewed.quaternion, &[0.0, 0.0, 0.0, 1.0]);
+
+        let mut singular = IDENTITY_3D;
+        singular[0] = 0.0;
+        assert_eq!(Decomposed3D::decompose(&singular), None);
+    }
+
+    #[test]
+    fn recomposes_3d_matrices() {
+        let mut perspective = IDENTITY_3D;
+        perspective[11] = -1.0 / 500.0;
+        for matrix in [
+            IDENTITY_3D,
+            compose_3d(&[
+                translate_3d(10.0, 20.0, -30.0),
+                rotate_z(60.0),
+                scale_3d(2.0, 3.0, 4.0),
+            ]),
+            compose_3d(&[rotate_x(-150.0), rotate_z(100.0), skew_x(30.0)]),
+            compose_3d(&[perspective, translate_3d(0.0, 0.0, 100.0), rotate_x(45.0)]),
+            // Flipped
+            compose_3d(&[rotate_z(30.0), scale_3d(-1.0, 1.0, 1.0)]),
+        ] {
+            // Matrices are normalized (so `m44` is 1), which is the same
+            // transform
+            let decomposed = Decomposed3D::decompose(&matrix).unwrap();
+            assert_close(&decomposed.recompose(), &matrix.map(|v| v / matrix[15]));
+        }
+    }
+
+    #[test]
+    fn interpolates_3d_matrices() {
+        let from = translate_3d(0.0, 0.0, 0.0);
+        let to = compose_3d(&[translate_3d(100.0, 0.0, 0.0), rotate_z(90.0)]);
+        assert_close(&Tweenable::interpolate(&from, &to, 0.0), &from);
+        assert_close(&Tweenable::interpolate(&from, &to, 1.0), &to);
+        assert_close(
+            &Tweenable::interpolate(&from, &to, 0.5),
+            &compose_3d(&[translate_3d(50.0, 0.0, 0.0), rotate_z(45.0)]),
+        );
+
+        // Singular matrices jump halfway through
+        let singular = scale_3d(0.0, 1.0, 1.0);
+        assert_eq!(Tweenable::interpolate(&to, &singular, 0.4), to);
+        assert_eq!(Tweenable::interpolate(&to, &singular, 0.6), singular);
+    }
+
+    #[test]
+    fn slerps_between_rotations() {
+        let from = z_quaternion(0.0);
+        let to = z_quaternion(90.0);
+        assert_close(&slerp(&from, &to, 0.0), &from);
+        assert_close(&slerp(&from, &to, 1.0), &to);
+        assert_close(&slerp(&from, &to, 0.5), &z_quaternion(45.0));
+        assert_close(&slerp(&to, &to, 0.5), &to);
+
+        // Past the ends, for easings that overshoot
+        assert_close(&slerp(&from, &to, 1.5), &z_quaternion(135.0));
+    }
+
+    #[test]
+    fn slerps_the_shortest_way() {
+        // 270° is -90° the short way around
+        let from = z_quaternion(0.0);
+        assert_close(
+            &slerp(&from, &z_quaternion(270.0), 0.5),
+            &z_quaternion(-45.0),
+        );
+
+        // `-q` is the same rotation as `q`, so there's nothing to rotate
+        let q = z_quaternion(60.0);
+        let antipodal = q.map(|value| -value);
+        for t in [0.0, 0.3, 1.0] {
+            assert_close(&slerp(&q, &antipodal, t), &q);
+        }
+
+        // Half turns are as long either way, so keep the direction given
+        assert_close(
+            &slerp(&from, &z_quaternion(180.0), 0.5),
+            &z_quaternion(90.0),
+        );
+        assert_close(
+            &slerp(&from, &z_quaternion(-180.0), 0.5),
+            &z_quaternion(-90.0),
+        );
+    }
+}
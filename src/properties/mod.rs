@@ -1,19 +1,36 @@
+mod angle;
+mod color;
+mod complex;
 mod css_property;
 mod css_transform;
 mod dom_attribute;
+mod matrix;
+mod orientation;
 mod svg_attribute;
 mod value;
 
+pub use angle::{Angle, RotationMode};
+pub use color::{ColorSpace, HueDirection};
+pub use complex::{ComplexToken, ComplexValue, ComplexValueError};
 pub use css_property::CssProperty;
 pub use css_transform::CssTransform;
-pub use dom_attribute::DomAttribute;
-pub use svg_attribute::SvgAttribute;
-pub use value::{Color, PropertyValue, Unit, Value, ValueOp};
+pub use orientation::Orientation;
+pub(crate) use value::KeyframeValue;
+pub use value::{
+    Color, Interpolation, PropertyKeyframe, PropertyValue, Tweenable, Unit, Value, ValueOp,
+    ValueParseError,
+};
 
-use crate::easings::Easing; // TODO: un-module these?
+use std::rc::Rc;
+
+use crate::{
+    builder::property::{Parameters, TweenParameters},
+    easings::Easing, // TODO: un-module these?
+    engine::{Animatable, Animation},
+};
 
 /// An animatable property value
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Property {
     CssProperty(CssProperty),
     CssTransform(CssTransform),
@@ -23,6 +40,62 @@ pub enum Property {
     // SvgAttribute(SvgAttribute),
 }
 
+impl Property {
+    /// Build the animation of this property for a single target
+    pub(crate) fn animation(
+        &self,
+        animatable: Option<Rc<Animatable>>,
+        params: &TweenParameters,
+    ) -> Animation {
+        match self {
+            Property::CssProperty(p) => p.animation(animatable, params),
+            Property::CssTransform(p) => p.animation(animatable, params),
+        }
+    }
+
+    /// Whether both are values for the same property
+    pub(crate) fn same_kind(&self, other: &Property) -> bool {
+        use std::mem::discriminant;
+        match (self, other) {
+            (Property::CssProperty(a), Property::CssProperty(b)) => {
+                discriminant(a) == discriminant(b)
+            }
+            (Property::CssTransform(a), Property::CssTransform(b)) => {
+                discriminant(a) == discriminant(b)
+            }
+            _ => false,
+        }
+    }
+
+    /// An empty keyframe chain for the same property
+    pub(crate) fn empty_keyframes(&self) -> Property {
+        match self {
+            Property::CssProperty(p) => Property::CssProperty(p.empty_keyframes()),
+            Property::CssTransform(p) => Property::CssTransform(p.empty_keyframes()),
+        }
+    }
+
+    /// Append keyframes reaching `value` to a keyframe chain (see
+    /// [`Property::empty_keyframes`]), or hold the previous value if `None`
+    pub(crate) fn push_keyframe(
+        &mut self,
+        value: Option<&Property>,
+        params: &Parameters,
+        offset: Option<f32>,
+    ) {
+        match (self, value) {
+            (Property::CssProperty(p), Some(Property::CssProperty(value))) => {
+                p.push_keyframe(Some(value), params, offset)
+            }
+            (Property::CssProperty(p), _) => p.push_keyframe(None, params, offset),
+            (Property::CssTransform(p), Some(Property::CssTransform(value))) => {
+                p.push_keyframe(Some(value), params, offset)
+            }
+            (Property::CssTransform(p), _) => p.push_keyframe(None, params, offset),
+        }
+    }
+}
+
 impl From<CssProperty> for Property {
     fn from(p: CssProperty) -> Self {
         Self::CssProperty(p)
@@ -60,6 +133,7 @@ impl From<CssTransform> for Property {
 
 // TODO: parameters mod?
 
+#[allow(dead_code)] // Superseded by `builder::property::Parameters` for now
 pub struct PropertyParameters<T> {
     value: PropertyValue<T>,
 
@@ -0,0 +1,242 @@
+//! 3D rotations, interpolated as quaternions (so without the gimbal lock of
+//! tweening `rotateX`, `rotateY` and `rotateZ` separately)
+
+use super::{matrix::slerp, value::parse_number, Angle, Tweenable, Unit, Value};
+
+/// An orientation in 3D, written as `rotate3d()`
+///
+/// Orientations are interpolated along the shortest arc between them
+/// (quaternion slerp), so they don't keep whole turns: `0deg` and `360deg`
+/// about the same axis are the same orientation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Orientation {
+    /// Unit quaternion `[x, y, z, w]`
+    quaternion: [f32; 4],
+}
+
+impl Orientation {
+    /// A rotation by `angle` (clockwise, looking towards the origin) about the
+    /// axis `[x, y, z]`, as `rotate3d(x, y, z, angle)`. Axes of zero length
+    /// don't rotate
+    pub fn axis_angle(x: f32, y: f32, z: f32, angle: Angle) -> Self {
+        let length = (x * x + y * y + z * z).sqrt();
+        if length < f32::EPSILON {
+            return Self::default();
+        }
+        let half = angle.to_degrees().to_radians() / 2.0;
+        let s = half.sin() / length;
+        Self {
+            quaternion: [x * s, y * s, z * s, half.cos()],
+        }
+    }
+
+    /// The rotations of `rotateX(x) rotateY(y) rotateZ(z)` combined
+    pub fn euler(x: Angle, y: Angle, z: Angle) -> Self {
+        Self::axis_angle(1.0, 0.0, 0.0, x)
+            .then(&Self::axis_angle(0.0, 1.0, 0.0, y))
+            .then(&Self::axis_angle(0.0, 0.0, 1.0, z))
+    }
+
+    /// The rotations of `self` and `other` combined, as `rotate3d(self)
+    /// rotate3d(other)`
+    pub fn then(&self, other: &Self) -> Self {
+        let [ax, ay, az, aw] = self.quaternion;
+        let [bx, by, bz, bw] = other.quaternion;
+        Self {
+            quaternion: [
+                aw * bx + ax * bw + ay * bz - az * by,
+                aw * by - ax * bz + ay * bw + az * bx,
+                aw * bz + ax * by - ay * bx + az * bw,
+                aw * bw - ax * bx - ay * by - az * bz,
+            ],
+        }
+    }
+
+    /// The opposite rotation
+    pub fn inverse(&self) -> Self {
+        let [x, y, z, w] = self.quaternion;
+        Self {
+            quaternion: [-x, -y, -z, w],
+        }
+    }
+
+    /// The rotation as a (unit) axis and an angle of at most 180°
+    pub fn to_axis_angle(&self) -> ([f32; 3], Angle) {
+        let [x, y, z, w] = match self.quaternion {
+            q if q[3] < -1e-6 => q.map(|value| -value),
+            q => q,
+        };
+        let s = (x * x + y * y + z * z).sqrt();
+        if s < 1e-6 {
+            return ([0.0, 0.0, 1.0], Angle::Degrees(0.0));
+        }
+        (
+            [x / s, y / s, z / s],
+            Angle::Degrees((2.0 * s.atan2(w)).to_degrees()),
+        )
+    }
+}
+
+impl Default for Orientation {
+    fn default() -> Self {
+        Self {
+            quaternion: [0.0, 0.0, 0.0, 1.0],
+        }
+    }
+}
+
+impl Tweenable for Orientation {
+    fn interpolate(from: &Self, to: &Self, t: f32) -> Self {
+        Self {
+            quaternion: slerp(&from.quaternion, &to.quaternion, t),
+        }
+    }
+
+    fn fmt_css(&self, _unit: Option<Unit>, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ([x, y, z], angle) = self.to_axis_angle();
+        write!(f, "{}, {}, {}, {}deg", x, y, z, angle.to_degrees())
+    }
+
+    /// The arguments of `rotate3d()`. Angles without a unit are taken as
+    /// degrees
+    fn parse_css(text: &str) -> Option<Value<Self>> {
+        let mut args = text.split(',').map(str::trim);
+        let mut axis = [0.0; 3];
+        for value in axis.iter_mut() {
+            *value = args.next()?.parse().ok()?;
+        }
+        let (angle, unit) = parse_number(args.next()?)?;
+        let angle = Angle::from_unit(angle, unit.unwrap_or(Unit::Deg))?;
+        match args.next() {
+            Some(_) => None,
+            None => Some(Value::from(Orientation::axis_angle(
+                axis[0], axis[1], axis[2], angle,
+            ))),
+        }
+    }
+
+    /// `self` rotated further by `other`
+    fn add(&self, other: &Self) -> Self {
+        self.then(other)
+    }
+
+    /// `self` rotated back by `other`
+    fn subtract(&self, other: &Self) -> Self {
+        self.then(&other.inverse())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_same(actual: &Orientation, expected: &Orientation) {
+        // `q` and `-q` are the same orientation
+        let product: f32 = (0..4)
+            .map(|i| actual.quaternion[i] * expected.quaternion[i])
+            .sum();
+        assert!(
+            (product.abs() - 1.0).abs() < 1e-5,
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    fn degrees(angle: f32) -> Angle {
+        Angle::Degrees(angle)
+    }
+
+    fn css(orientation: &Orientation) -> String {
+        Value::from(*orientation).to_string()
+    }
+
+    #[test]
+    fn converts_to_axis_angle() {
+        let orientation = Orientation::axis_angle(0.0, 2.0, 0.0, Angle::Turns(0.25));
+        let (axis, angle) = orientation.to_axis_angle();
+        assert_eq!(axis, [0.0, 1.0, 0.0]);
+        assert!((angle.to_degrees() - 90.0).abs() < 1e-4);
+
+        // Angles over 180° are the other way about the opposite axis
+        let (axis, angle) = Orientation::axis_angle(1.0, 0.0, 0.0, degrees(270.0)).to_axis_angle();
+        assert_eq!(axis, [-1.0, 0.0, 0.0]);
+        assert!((angle.to_degrees() - 90.0).abs() < 1e-4);
+
+        assert_eq!(
+            Orientation::axis_angle(0.0, 0.0, 0.0, degrees(90.0)),
+            Orientation::default()
+        );
+        assert_eq!(
+            Orientation::default().to_axis_angle(),
+            ([0.0, 0.0, 1.0], degrees(0.0))
+        );
+    }
+
+    #[test]
+    fn combines_rotations() {
+        let x = Orientation::axis_angle(1.0, 0.0, 0.0, degrees(30.0));
+        let z = Orientation::axis_angle(0.0, 0.0, 1.0, degrees(-60.0));
+        assert_same(
+            &Orientation::euler(degrees(30.0), degrees(0.0), degrees(-60.0)),
+            &x.then(&z),
+        );
+        assert_same(
+            &x.then(&x).then(&x),
+            &Orientation::axis_angle(1.0, 0.0, 0.0, degrees(90.0)),
+        );
+        assert_same(&x.then(&z).then(&z.inverse()), &x);
+        assert_same(&x.then(&x.inverse()), &Orientation::default());
+        assert_same(&x.add(&z).subtract(&z), &x);
+    }
+
+    #[test]
+    fn parses_and_formats_rotate3d() {
+        let parse = |text: &str| Orientation::parse_css(text).map(|value| *value.value());
+        assert_same(
+            &parse("0, 0, 1, 0.25turn").unwrap(),
+            &Orientation::axis_angle(0.0, 0.0, 1.0, degrees(90.0)),
+        );
+        // Degrees without a unit
+        assert_same(
+            &parse(" 1,1,0 , 45 ").unwrap(),
+            &Orientation::axis_angle(1.0, 1.0, 0.0, degrees(45.0)),
+        );
+        for text in [
+            "",
+            "0, 0, 1",
+            "0, 0, 1, 90deg, 1",
+            "0, 0, z, 90deg",
+            "0, 0, 1, 90px",
+        ] {
+            assert_eq!(parse(text), None, "{:?}", text);
+        }
+
+        let orientation = Orientation::axis_angle(0.0, 0.0, 1.0, degrees(90.0));
+        assert_eq!(css(&orientation), "0, 0, 1, 90deg");
+        assert_same(&parse(&css(&orientation)).unwrap(), &orientation);
+    }
+
+    #[test]
+    fn interpolates_the_shortest_way() {
+        let from = Orientation::default();
+        let to = Orientation::axis_angle(0.0, 1.0, 0.0, degrees(120.0));
+        assert_same(&Orientation::interpolate(&from, &to, 0.0), &from);
+        assert_same(&Orientation::interpolate(&from, &to, 1.0), &to);
+        assert_same(
+            &Orientation::interpolate(&from, &to, 0.25),
+            &Orientation::axis_angle(0.0, 1.0, 0.0, degrees(30.0)),
+        );
+
+        // 270° about z is 90° the other way
+        let to = Orientation::axis_angle(0.0, 0.0, 1.0, degrees(270.0));
+        assert_same(
+            &Orientation::interpolate(&from, &to, 0.5),
+            &Orientation::axis_angle(0.0, 0.0, 1.0, degrees(-45.0)),
+        );
+
+        // Whole turns are no rotation at all
+        let to = Orientation::axis_angle(1.0, 0.0, 0.0, degrees(360.0));
+        assert_same(&Orientation::interpolate(&from, &to, 0.5), &from);
+    }
+}
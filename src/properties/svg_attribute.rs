@@ -1 +1,2 @@
+#[allow(dead_code)] // TODO: animate svg attributes
 pub enum SvgAttribute {}
@@ -1,25 +1,34 @@
-use std::fmt::{Debug, Display};
+use std::{
+    fmt::{Debug, Display},
+    str::FromStr,
+};
 
 use lazy_static::lazy_static;
 use regex::Regex;
 
-use crate::target::Target;
+use super::{
+    angle::RotationMode,
+    color::{self, ColorSpace},
+};
+use crate::{builder::property::Parameters, easings::Easing};
 
 lazy_static! {
     static ref UNIT_REGEX: Regex = Regex::new(
-        r"/[+-]?\d*\.?\d+(?:\.\d+)?(?:[eE][+-]?\d+)?(%|px|pt|em|rem|in|cm|mm|ex|ch|pc|vw|vh|vmin|vmax|deg|rad|turn)?$/"
+        r"^\s*([+-]?\d*\.?\d+(?:[eE][+-]?\d+)?)(%|px|pt|em|rem|in|cm|mm|ex|ch|pc|vw|vh|vmin|vmax|deg|grad|rad|turn)?\s*$"
     ).expect("Failed to compile UNIT_REGEX");
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Value<T>(T, Option<Unit>); // TODO: just for input, internal repr no option?
 
-impl<T: PartialOrd> PartialOrd for Value<T> {
+impl<T: PartialOrd + Tweenable> PartialOrd for Value<T> {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         match (self.1, other.1) {
             (Some(self_unit), Some(other_unit)) if self_unit == other_unit => {
                 self.0.partial_cmp(&other.0)
             }
+            // Units that can be converted without measuring an element
+            (Some(self_unit), Some(_)) => self.0.partial_cmp(&other.convert(self_unit)?.0),
             (None, None) => self.0.partial_cmp(&other.0),
 
             _ => None,
@@ -27,6 +36,242 @@ impl<T: PartialOrd> PartialOrd for Value<T> {
     }
 }
 
+impl<T> Value<T> {
+    pub fn new(value: T, unit: Option<Unit>) -> Self {
+        Value(value, unit)
+    }
+
+    pub fn value(&self) -> &T {
+        &self.0
+    }
+
+    pub fn unit(&self) -> Option<Unit> {
+        self.1
+    }
+}
+
+impl<T: Tweenable> Value<T> {
+    /// The value in another unit, for absolute lengths (px, in, cm, mm, pt and
+    /// pc) and angles. Values without a unit can't be converted
+    pub fn convert(&self, to: Unit) -> Option<Value<T>> {
+        self.convert_with(to, Unit::factor)
+    }
+
+    /// The value in another unit, using `factor` to find the factor between
+    /// two units
+    pub(crate) fn convert_with<F>(&self, to: Unit, factor: F) -> Option<Value<T>>
+    where
+        F: Fn(Unit, Unit) -> Option<f32>,
+    {
+        let factor = factor(self.1?, to)?;
+        Some(Value(self.0.scale(factor), Some(to)))
+    }
+}
+
+impl<T: Tweenable> Display for Value<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt_css(self.1, f)
+    }
+}
+
+/// Options for how some types are interpolated, set per property
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Interpolation {
+    /// The color space colors are interpolated in
+    pub color_space: ColorSpace,
+    /// Which way angles (and hues) go around, if not literally from one value
+    /// to the other (for angles) or as set by the color space (for hues)
+    pub rotation: Option<RotationMode>,
+}
+
+/// A value type that can be animated between two values
+pub trait Tweenable: 'static + Debug + Clone {
+    /// The value at (eased) progress `t` between `from` and `to`.
+    ///
+    /// Note that `t` may fall outside of `[0, 1]` for some easings
+    fn interpolate(from: &Self, to: &Self, t: f32) -> Self;
+
+    /// [`Tweenable::interpolate`] with options for specific types (see
+    /// [`Interpolation`]). Types they don't apply to ignore them
+    fn interpolate_with(from: &Self, to: &Self, t: f32, _options: &Interpolation) -> Self {
+        Self::interpolate(from, to, t)
+    }
+
+    /// Round the value to increments of `1 / round` (e.g. `round = 10` rounds
+    /// to one decimal place). Types without a fractional part can ignore this
+    fn round(&self, _round: u32) -> Self {
+        self.clone()
+    }
+
+    /// Write the value as it should be set on the DOM
+    fn fmt_css(&self, unit: Option<Unit>, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result;
+
+    /// Read a value as given by the DOM (e.g. from the computed style). Types
+    /// that can't be read return `None`
+    fn parse_css(_text: &str) -> Option<Value<Self>> {
+        None
+    }
+
+    /// `self + other`, for `+=` values. Types that can't be added keep their
+    /// current value
+    fn add(&self, _other: &Self) -> Self {
+        self.clone()
+    }
+
+    /// `self - other`, for `-=` values
+    fn subtract(&self, _other: &Self) -> Self {
+        self.clone()
+    }
+
+    /// `self * other`, for `*=` values
+    fn multiply(&self, _other: &Self) -> Self {
+        self.clone()
+    }
+
+    /// Multiply the value by `factor`, to convert it between units. Types
+    /// without units keep their current value
+    fn scale(&self, _factor: f32) -> Self {
+        self.clone()
+    }
+}
+
+/// Split css text like `"12.5px"` into its number and unit
+pub(crate) fn parse_number(text: &str) -> Option<(f32, Option<Unit>)> {
+    let captures = UNIT_REGEX.captures(text)?;
+    let number = captures[1].parse().ok()?;
+    let unit = captures
+        .get(2)
+        .and_then(|unit| Unit::from_suffix(unit.as_str()));
+    Some((number, unit))
+}
+
+/// Error parsing a value from a string (e.g. `"250px"` or `"+=20%"`)
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("invalid value: {0:?}")]
+pub struct ValueParseError(String);
+
+impl<T: Tweenable> FromStr for Value<T> {
+    type Err = ValueParseError;
+
+    /// Parse a value with an optional unit, e.g. `"250px"`, `"1turn"` or `"0.5"`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        T::parse_css(s).ok_or_else(|| ValueParseError(s.to_string()))
+    }
+}
+
+impl<T: Tweenable> FromStr for PropertyValue<T> {
+    type Err = ValueParseError;
+
+    /// Parse a value (see [`Value::from_str`]), or a value relative to the
+    /// current one: `"+=20%"`, `"-=10px"` or `"*=2"`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let parse = |operand: &str| {
+            operand
+                .parse::<Value<T>>()
+                .map_err(|_| ValueParseError(s.to_string()))
+        };
+        if let Some(operand) = s.strip_prefix("+=") {
+            Ok(PropertyValue::Relative(ValueOp::Add(parse(operand)?)))
+        } else if let Some(operand) = s.strip_prefix("-=") {
+            Ok(PropertyValue::Relative(ValueOp::Subtract(parse(operand)?)))
+        } else if let Some(operand) = s.strip_prefix("*=") {
+            Ok(PropertyValue::Relative(ValueOp::Multiply(parse(operand)?)))
+        } else {
+            Ok(PropertyValue::Exact(s.parse()?))
+        }
+    }
+}
+
+impl Tweenable for f32 {
+    fn interpolate(from: &Self, to: &Self, t: f32) -> Self {
+        from + (t * (to - from))
+    }
+
+    fn round(&self, round: u32) -> Self {
+        let round = round as f32;
+        (self * round).round() / round
+    }
+
+    fn fmt_css(&self, unit: Option<Unit>, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self, unit.map(Unit::as_str).unwrap_or_default())
+    }
+
+    fn parse_css(text: &str) -> Option<Value<Self>> {
+        let (number, unit) = parse_number(text)?;
+        Some(Value(number, unit))
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        self + other
+    }
+
+    fn subtract(&self, other: &Self) -> Self {
+        self - other
+    }
+
+    fn multiply(&self, other: &Self) -> Self {
+        self * other
+    }
+
+    fn scale(&self, factor: f32) -> Self {
+        self * factor
+    }
+}
+
+impl Tweenable for i32 {
+    fn interpolate(from: &Self, to: &Self, t: f32) -> Self {
+        f32::interpolate(&(*from as f32), &(*to as f32), t).round() as i32
+    }
+
+    fn fmt_css(&self, unit: Option<Unit>, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self, unit.map(Unit::as_str).unwrap_or_default())
+    }
+
+    fn parse_css(text: &str) -> Option<Value<Self>> {
+        let (number, unit) = parse_number(text)?;
+        Some(Value(number.round() as i32, unit))
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        self + other
+    }
+
+    fn subtract(&self, other: &Self) -> Self {
+        self - other
+    }
+
+    fn multiply(&self, other: &Self) -> Self {
+        self * other
+    }
+
+    fn scale(&self, factor: f32) -> Self {
+        (*self as f32 * factor).round() as i32
+    }
+}
+
+impl Tweenable for Color {
+    fn interpolate(from: &Self, to: &Self, t: f32) -> Self {
+        color::interpolate(from, to, t, &Interpolation::default())
+    }
+
+    fn interpolate_with(from: &Self, to: &Self, t: f32, options: &Interpolation) -> Self {
+        color::interpolate(from, to, t, options)
+    }
+
+    fn fmt_css(&self, _unit: Option<Unit>, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "rgba({}, {}, {}, {})",
+            self.red, self.green, self.blue, self.alpha
+        )
+    }
+
+    fn parse_css(text: &str) -> Option<Value<Self>> {
+        crate::css::parse_color(text).ok().map(Value::from)
+    }
+}
+
 impl<T> From<T> for Value<T> {
     fn from(t: T) -> Self {
         Value(t, None)
@@ -39,7 +284,7 @@ impl<T> From<(T, Unit)> for Value<T> {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum PropertyValue<T> {
     // TODO: Remove?
     Exact(Value<T>),
@@ -80,20 +325,184 @@ impl<T> From<(T, Unit)> for PropertyValue<T> {
 //     }
 // }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct PropertyKeyframe<T> {
-    value: Value<T>,
-    // TODO: keyframe params
+    /// Value at the end of the keyframe, or `None` to hold the previous value
+    pub(crate) value: Option<KeyframeValue<T>>,
+    pub(crate) params: Parameters,
+    /// Position as a fraction of the property's duration, if placed by offset
+    pub(crate) offset: Option<f32>,
+}
+
+impl<T> PropertyKeyframe<T> {
+    /// A keyframe that holds the previous value, until given one with
+    /// [`PropertyKeyframe::value`]
+    pub fn new() -> Self {
+        Self {
+            value: None,
+            params: Parameters::default(),
+            offset: None,
+        }
+    }
+
+    /// Value at the end of the keyframe
+    pub fn value<V: Into<Value<T>>>(mut self, value: V) -> Self {
+        self.value = Some(KeyframeValue::Exact(value.into()));
+        self
+    }
+
+    /// Value at the end of the keyframe, relative to the previous keyframe's
+    /// (e.g. `ValueOp::Add((100.0, Unit::Px).into())` for `"+=100px"`)
+    pub fn relative(mut self, op: ValueOp<T>) -> Self {
+        self.value = Some(KeyframeValue::Relative(op));
+        self
+    }
+
+    /// Jump to `from` at the start of the keyframe, then tween to `to`
+    pub fn range<V: Into<Value<T>>>(mut self, from: V, to: V) -> Self {
+        self.value = Some(KeyframeValue::Range(from.into(), to.into()));
+        self
+    }
+
+    /// Place the keyframe at a fraction (`0.0` to `1.0`) of the property's
+    /// duration, like a percentage in CSS `@keyframes`.
+    ///
+    /// If any keyframe has an offset, keyframes without one are spaced evenly
+    /// between their neighbours (with the first at `0.0` and the last at `1.0`)
+    /// and the keyframes' own durations and delays are ignored. Each keyframe's
+    /// easing applies to the segment leading up to it
+    pub fn offset(mut self, offset: f32) -> Self {
+        self.offset = Some(offset.clamp(0.0, 1.0));
+        self
+    }
+
+    /// Duration of the keyframe (defaults to an even split of the property's
+    /// duration)
+    pub fn duration(mut self, value: u32) -> Self {
+        self.params.duration = Some(value);
+        self
+    }
+    /// Delay before the keyframe starts (defaults to the property's delay for
+    /// the first keyframe, and none for the rest)
+    pub fn delay(mut self, value: u32) -> Self {
+        self.params.delay = Some(value);
+        self
+    }
+    /// Delay after the keyframe ends (defaults to the property's end delay for
+    /// the last keyframe, and none for the rest)
+    pub fn end_delay(mut self, value: u32) -> Self {
+        self.params.end_delay = Some(value);
+        self
+    }
+    pub fn easing(mut self, value: Easing) -> Self {
+        self.params.easing = Some(value);
+        self
+    }
+    pub fn round(mut self, value: u32) -> Self {
+        self.params.round = Some(value);
+        self
+    }
+}
+
+/// What a [`PropertyKeyframe`] tweens to
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) enum KeyframeValue<T> {
+    Exact(Value<T>),
+    /// Relative to the previous keyframe's value
+    Relative(ValueOp<T>),
+    /// From the first value (rather than the previous keyframe's) to the second
+    Range(Value<T>, Value<T>),
+}
+
+impl<T> Default for PropertyKeyframe<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> From<Vec<PropertyKeyframe<T>>> for PropertyValue<T> {
+    fn from(keyframes: Vec<PropertyKeyframe<T>>) -> Self {
+        PropertyValue::Keyframes(keyframes)
+    }
 }
 
-#[derive(Debug, PartialEq)]
+impl<T: Clone> PropertyKeyframe<T> {
+    /// Keyframes reaching `value` (or holding the previous value, if `None`)
+    pub(crate) fn from_value(
+        value: Option<&PropertyValue<T>>,
+        params: &Parameters,
+        offset: Option<f32>,
+    ) -> Vec<Self> {
+        let keyframe = |value: Option<KeyframeValue<T>>| PropertyKeyframe {
+            value,
+            params: params.clone(),
+            offset,
+        };
+        match value {
+            None => vec![keyframe(None)],
+            Some(PropertyValue::Exact(to)) => {
+                vec![keyframe(Some(KeyframeValue::Exact(to.clone())))]
+            }
+            Some(PropertyValue::Range(from, to)) => vec![keyframe(Some(KeyframeValue::Range(
+                from.clone(),
+                to.clone(),
+            )))],
+            Some(PropertyValue::Relative(op)) => {
+                vec![keyframe(Some(KeyframeValue::Relative(op.clone())))]
+            }
+            Some(PropertyValue::Keyframes(keyframes)) => keyframes
+                .iter()
+                .map(|k| PropertyKeyframe {
+                    value: k.value.clone(),
+                    params: k.params.or(params),
+                    offset: k.offset,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
 pub enum ValueOp<T> {
     Add(Value<T>),
     Subtract(Value<T>),
     Multiply(Value<T>),
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+impl<T> ValueOp<T> {
+    /// The value the current one is combined with
+    pub fn operand(&self) -> &Value<T> {
+        match self {
+            ValueOp::Add(operand) | ValueOp::Subtract(operand) | ValueOp::Multiply(operand) => {
+                operand
+            }
+        }
+    }
+
+    /// The same operation, with another operand
+    pub(crate) fn with_operand(&self, operand: Value<T>) -> Self {
+        match self {
+            ValueOp::Add(_) => ValueOp::Add(operand),
+            ValueOp::Subtract(_) => ValueOp::Subtract(operand),
+            ValueOp::Multiply(_) => ValueOp::Multiply(operand),
+        }
+    }
+}
+
+impl<T: Tweenable> ValueOp<T> {
+    /// Apply the operation to the `current` value, keeping its unit unless the
+    /// operand has one
+    pub(crate) fn resolve(&self, current: &Value<T>) -> Value<T> {
+        let (value, operand) = match self {
+            ValueOp::Add(operand) => (current.0.add(&operand.0), operand),
+            ValueOp::Subtract(operand) => (current.0.subtract(&operand.0), operand),
+            ValueOp::Multiply(operand) => (current.0.multiply(&operand.0), operand),
+        };
+        Value(value, operand.1.or(current.1))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum Unit {
     Dimensionless, // (f32),
 
@@ -113,13 +522,82 @@ pub enum Unit {
     Vmin,
     Vmax,
     Deg,
+    Grad,
     Rad,
     Turn,
 }
 
 impl Unit {
-    fn from_value(text: &str) -> Option<Unit> {
-        match UNIT_REGEX.captures(text)?.get(1)?.as_str() {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Unit::Dimensionless => "",
+            Unit::Percent => "%",
+            Unit::Px => "px",
+            Unit::Pt => "pt",
+            Unit::Em => "em",
+            Unit::Rem => "rem",
+            Unit::In => "in",
+            Unit::Cm => "cm",
+            Unit::Mm => "mm",
+            Unit::Ex => "ex",
+            Unit::Ch => "ch",
+            Unit::Pc => "pc",
+            Unit::Vw => "vw",
+            Unit::Vh => "vh",
+            Unit::Vmin => "vmin",
+            Unit::Vmax => "vmax",
+            Unit::Deg => "deg",
+            Unit::Grad => "grad",
+            Unit::Rad => "rad",
+            Unit::Turn => "turn",
+        }
+    }
+
+    /// Size of an absolute length in px (`1in` is `96px`, etc.)
+    pub fn absolute_px(self) -> Option<f32> {
+        match self {
+            Unit::Px => Some(1.0),
+            Unit::In => Some(96.0),
+            Unit::Cm => Some(96.0 / 2.54),
+            Unit::Mm => Some(96.0 / 25.4),
+            Unit::Pt => Some(96.0 / 72.0),
+            Unit::Pc => Some(16.0),
+            _ => None,
+        }
+    }
+
+    /// Size of an angle in degrees
+    fn degrees(self) -> Option<f32> {
+        match self {
+            Unit::Deg => Some(1.0),
+            Unit::Grad => Some(0.9),
+            Unit::Rad => Some(180.0 / std::f32::consts::PI),
+            Unit::Turn => Some(360.0),
+            _ => None,
+        }
+    }
+
+    /// What to multiply a value in `from` units by to convert it to `to`
+    /// units, if it can be done without measuring an element (between
+    /// absolute lengths, or between angles)
+    pub fn factor(from: Unit, to: Unit) -> Option<f32> {
+        if from == to {
+            return Some(1.0);
+        }
+        match (from.absolute_px(), to.absolute_px()) {
+            (Some(from), Some(to)) => Some(from / to),
+            _ => Some(from.degrees()? / to.degrees()?),
+        }
+    }
+
+    /// The unit of css text like `"12.5px"`, if it has one
+    pub fn from_value(text: &str) -> Option<Unit> {
+        Unit::from_suffix(UNIT_REGEX.captures(text)?.get(2)?.as_str())
+    }
+
+    /// The unit for a suffix like `"px"`
+    fn from_suffix(suffix: &str) -> Option<Unit> {
+        match suffix {
             "%" => Some(Unit::Percent),
             "px" => Some(Unit::Px),
             "pt" => Some(Unit::Pt),
@@ -136,6 +614,7 @@ impl Unit {
             "vmin" => Some(Unit::Vmin),
             "vmax" => Some(Unit::Vmax),
             "deg" => Some(Unit::Deg),
+            "grad" => Some(Unit::Grad),
             "rad" => Some(Unit::Rad),
             "turn" => Some(Unit::Turn),
             _ => None,
@@ -143,6 +622,14 @@ impl Unit {
     }
 }
 
+impl FromStr for Unit {
+    type Err = ValueParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Unit::from_suffix(s.trim()).ok_or_else(|| ValueParseError(s.to_string()))
+    }
+}
+
 // #[derive(Debug, PartialEq)]
 // pub enum Color {
 //     Rgb,
@@ -156,49 +643,6 @@ impl Unit {
 
 pub type Color = palette::Alpha<palette::Srgb<u8>, f32>;
 
-/* Get Relative Value, just replace with a closure?
-
-function getRelativeValue(to, from) {
-  var operator = /^(\*=|\+=|-=)/.exec(to);
-  if (!operator) { return to; }
-  var u = getUnit(to) || 0;
-  var x = parseFloat(from);
-  var y = parseFloat(to.replace(operator[0], ''));
-  switch (operator[0][0]) {
-    case '+': return x + y + u;
-    case '-': return x - y + u;
-    case '*': return x * y + u;
-  }
-}
-
-*/
-
-// type ColorValue = palette::Alpha<palette::Srgb<u8>, f32>;
-
-// enum Value {
-//     Color(ColorValue),
-// }
-
-// impl Value {
-//     pub fn color<C: Into<ColorValue>>(c: C) -> Self {
-//         Self::Color(c.into())
-//     }
-// }
-
-// impl Display for Value {
-//     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-//         match self {
-//             Value::Color(color) => {
-//                 write!(
-//                     f,
-//                     "rgba({}, {}, {}, {})",
-//                     color.red, color.green, color.blue, color.alpha
-//                 )
-//             }
-//         }
-//     }
-// }
-
 // TODO: remove
 // pub enum OldValue<T> {
 //     Dimensionless(T),
@@ -222,3 +666,119 @@ function getRelativeValue(to, from) {
 
 //     Function(Box<dyn Fn(web_sys::Element, u32, u32) -> Value<T>>),
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: Option<f32>, expected: f32) {
+        let actual = actual.expect("no factor");
+        assert!(
+            (actual - expected).abs() < 1e-4,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn sizes_absolute_lengths() {
+        assert_eq!(Unit::Px.absolute_px(), Some(1.0));
+        assert_eq!(Unit::In.absolute_px(), Some(96.0));
+        assert_eq!(Unit::Pc.absolute_px(), Some(16.0));
+        assert_close(Unit::Cm.absolute_px(), 37.795_277);
+        assert_close(Unit::Mm.absolute_px(), 3.779_527_7);
+        assert_close(Unit::Pt.absolute_px(), 4.0 / 3.0);
+
+        // Relative to something that has to be measured
+        for unit in [Unit::Percent, Unit::Em, Unit::Rem, Unit::Vw, Unit::Deg] {
+            assert_eq!(unit.absolute_px(), None, "{:?}", unit);
+        }
+    }
+
+    #[test]
+    fn converts_between_lengths() {
+        assert_eq!(Unit::factor(Unit::In, Unit::Px), Some(96.0));
+        assert_eq!(Unit::factor(Unit::Px, Unit::In), Some(1.0 / 96.0));
+        assert_eq!(Unit::factor(Unit::Pt, Unit::Pt), Some(1.0));
+        assert_close(Unit::factor(Unit::In, Unit::Cm), 2.54);
+        assert_close(Unit::factor(Unit::Cm, Unit::Mm), 10.0);
+        assert_close(Unit::factor(Unit::In, Unit::Pt), 72.0);
+        assert_close(Unit::factor(Unit::Pc, Unit::Pt), 12.0);
+    }
+
+    #[test]
+    fn converts_between_angles() {
+        assert_eq!(Unit::factor(Unit::Turn, Unit::Deg), Some(360.0));
+        assert_close(Unit::factor(Unit::Deg, Unit::Grad), 10.0 / 9.0);
+        assert_close(Unit::factor(Unit::Rad, Unit::Deg), 57.295_78);
+        assert_close(Unit::factor(Unit::Turn, Unit::Rad), std::f32::consts::TAU);
+    }
+
+    #[test]
+    fn doesnt_convert_without_measuring() {
+        assert_eq!(Unit::factor(Unit::Percent, Unit::Px), None);
+        assert_eq!(Unit::factor(Unit::Px, Unit::Em), None);
+        assert_eq!(Unit::factor(Unit::Vw, Unit::Vh), None);
+        assert_eq!(Unit::factor(Unit::Px, Unit::Deg), None);
+        assert_eq!(Unit::factor(Unit::Deg, Unit::Dimensionless), None);
+        assert_eq!(Unit::factor(Unit::Em, Unit::Em), Some(1.0));
+    }
+
+    #[test]
+    fn parses_values() {
+        assert_eq!("250px".parse(), Ok(Value::new(250.0, Some(Unit::Px))));
+        assert_eq!(
+            " -1.5e2vmin ".parse(),
+            Ok(Value::new(-150.0, Some(Unit::Vmin)))
+        );
+        assert_eq!(".5".parse(), Ok(Value::new(0.5, None)));
+        assert_eq!("10%".parse(), Ok(Value::new(10, Some(Unit::Percent))));
+        assert_eq!("1turn".parse(), Ok(Value::from(crate::Angle::Turns(1.0))));
+        // Angles without a unit are degrees
+        assert_eq!("45".parse(), Ok(Value::from(crate::Angle::Degrees(45.0))));
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        for text in ["", "px", "10 px", "10furlongs", "1.2.3", "+=10"] {
+            assert_eq!(
+                text.parse::<Value<f32>>(),
+                Err(ValueParseError(text.to_string()))
+            );
+        }
+        assert!("10px".parse::<Value<crate::Angle>>().is_err());
+    }
+
+    #[test]
+    fn parses_relative_values() {
+        let parse = |s: &str| s.parse::<PropertyValue<f32>>();
+        assert_eq!(
+            parse("+=20%"),
+            Ok(PropertyValue::Relative(ValueOp::Add(Value::new(
+                20.0,
+                Some(Unit::Percent)
+            ))))
+        );
+        assert_eq!(
+            parse(" -=10px"),
+            Ok(PropertyValue::Relative(ValueOp::Subtract(Value::new(
+                10.0,
+                Some(Unit::Px)
+            ))))
+        );
+        assert_eq!(
+            parse("*=2"),
+            Ok(PropertyValue::Relative(ValueOp::Multiply(Value::new(
+                2.0, None
+            ))))
+        );
+        assert_eq!(
+            parse("-10px"),
+            Ok(PropertyValue::Exact(Value::new(-10.0, Some(Unit::Px))))
+        );
+        // The error is for the whole value
+        assert_eq!(parse("+=em"), Err(ValueParseError("+=em".to_string())));
+        assert!(parse("/=2").is_err());
+    }
+}
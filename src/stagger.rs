@@ -0,0 +1,394 @@
+//! Staggering values across the targets of an animation
+//!
+//! See https://animejs.com/documentation/#staggeringBasics
+
+use std::cell::RefCell;
+
+use crate::{
+    easings::Easing,
+    properties::{Unit, Value},
+    target::Target,
+};
+
+/// A value to stagger: either increasing by a fixed amount for each target, or
+/// spread evenly across a range
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stagger<T> {
+    /// `start + value * distance` for each target
+    Value(T),
+
+    /// Spread from the first to the second value
+    Range(T, T),
+}
+
+impl<T: Staggerable> From<T> for Stagger<T> {
+    fn from(value: T) -> Self {
+        Stagger::Value(value)
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StaggerProps {
+    start: Option<f32>,
+    from: Option<StaggerFrom>,
+    direction: Option<StaggerDirection>,
+    easing: Option<Easing>,
+    grid: Option<(u32, u32)>,
+    axis: Option<StaggerGridAxis>,
+}
+
+impl StaggerProps {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Value to start staggering from (defaults to 0, or the start of the
+    /// range)
+    pub fn start(mut self, value: f32) -> Self {
+        self.start = Some(value);
+        self
+    }
+
+    /// Which target the stagger starts from
+    pub fn from(mut self, value: StaggerFrom) -> Self {
+        self.from = Some(value);
+        self
+    }
+
+    pub fn direction(mut self, value: StaggerDirection) -> Self {
+        self.direction = Some(value);
+        self
+    }
+
+    /// Space the values along an easing curve, instead of linearly
+    pub fn easing(mut self, value: Easing) -> Self {
+        self.easing = Some(value);
+        self
+    }
+
+    /// Treat the targets as a grid (of `columns` by `rows`), staggering by the
+    /// distance between them
+    pub fn grid(mut self, columns: u32, rows: u32) -> Self {
+        self.grid = Some((columns, rows));
+        self
+    }
+
+    /// Only stagger along one axis of the grid
+    pub fn axis(mut self, value: StaggerGridAxis) -> Self {
+        self.axis = Some(value);
+        self
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum StaggerFrom {
+    #[default]
+    First,
+    Last,
+    Center,
+    Index(u32),
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum StaggerDirection {
+    #[default]
+    Normal,
+    Reverse,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StaggerGridAxis {
+    X,
+    Y,
+}
+
+/// A value that can be staggered: a number, optionally with a unit
+pub trait Staggerable: 'static + Clone {
+    /// The numeric part of the value, and its unit (if any)
+    fn split(&self) -> (f32, Option<Unit>);
+
+    /// Rebuild a value from a staggered number and the original unit
+    fn join(value: f32, unit: Option<Unit>) -> Self;
+}
+
+impl Staggerable for f32 {
+    fn split(&self) -> (f32, Option<Unit>) {
+        (*self, None)
+    }
+
+    fn join(value: f32, _unit: Option<Unit>) -> Self {
+        value
+    }
+}
+
+impl Staggerable for i32 {
+    fn split(&self) -> (f32, Option<Unit>) {
+        (*self as f32, None)
+    }
+
+    fn join(value: f32, _unit: Option<Unit>) -> Self {
+        value.round() as i32
+    }
+}
+
+impl Staggerable for u32 {
+    fn split(&self) -> (f32, Option<Unit>) {
+        (*self as f32, None)
+    }
+
+    fn join(value: f32, _unit: Option<Unit>) -> Self {
+        value.round().max(0.0) as u32
+    }
+}
+
+impl<T: Staggerable> Staggerable for Value<T> {
+    fn split(&self) -> (f32, Option<Unit>) {
+        let (value, unit) = self.value().split();
+        (value, self.unit().or(unit))
+    }
+
+    fn join(value: f32, unit: Option<Unit>) -> Self {
+        Value::new(T::join(value, None), unit)
+    }
+}
+
+/// Stagger a value across the targets of an animation.
+///
+/// The returned function can be passed to [`Builder::delay_fn`],
+/// [`Builder::duration_fn`] or any of the `*_fn` property methods, e.g.
+/// `.delay_fn(stagger(100, StaggerProps::new().from(StaggerFrom::Center)))`
+///
+/// [`Builder::delay_fn`]: crate::builder::Builder::delay_fn
+/// [`Builder::duration_fn`]: crate::builder::Builder::duration_fn
+pub fn stagger<T: Staggerable, S: Into<Stagger<T>>>(
+    value: S,
+    props: StaggerProps,
+) -> impl Fn(&Target, usize, usize) -> T {
+    let (val1, val2, unit, is_range) = match value.into() {
+        Stagger::Value(value) => {
+            let (value, unit) = value.split();
+            (value, 0.0, unit, false)
+        }
+        Stagger::Range(from, to) => {
+            let (from, _) = from.split();
+            let (to, unit) = to.split();
+            (from, to, unit, true)
+        }
+    };
+    let start = props.start.unwrap_or(if is_range { val1 } else { 0.0 });
+
+    // Distances are the same for every target, so only calculate them once
+    // (per number of targets)
+    let distances: RefCell<Option<(usize, Vec<f32>, f32)>> = RefCell::new(None);
+
+    move |_target, index, total| {
+        let mut cache = distances.borrow_mut();
+        if !matches!(&*cache, Some((cached, _, _)) if *cached == total) {
+            let (values, max) = props.distances(total);
+            *cache = Some((total, values, max));
+        }
+        let (_, values, max) = cache.as_ref().unwrap();
+
+        let spacing = if !is_range {
+            val1
+        } else if *max != 0.0 {
+            (val2 - val1) / max
+        } else {
+            0.0
+        };
+        let distance = values.get(index).copied().unwrap_or_default();
+        T::join(start + spacing * ((distance * 100.0).round() / 100.0), unit)
+    }
+}
+
+impl StaggerProps {
+    /// The (unscaled) distance of each of `total` targets from the starting
+    /// target, along with the largest distance
+    fn distances(&self, total: usize) -> (Vec<f32>, f32) {
+        let from = self.from.unwrap_or_default();
+        let from_center = from == StaggerFrom::Center;
+        let from_index = match from {
+            StaggerFrom::First => 0.0,
+            StaggerFrom::Last => total as f32 - 1.0,
+            StaggerFrom::Center => (total as f32 - 1.0) / 2.0,
+            StaggerFrom::Index(i) => i as f32,
+        };
+
+        let mut values: Vec<f32> = (0..total)
+            .map(|index| {
+                let index = index as f32;
+                match self.grid {
+                    None => (from_index - index).abs(),
+                    Some((columns, rows)) => {
+                        let (columns, rows) = (columns as f32, rows as f32);
+                        let (from_x, from_y) = if from_center {
+                            ((columns - 1.0) / 2.0, (rows - 1.0) / 2.0)
+                        } else {
+                            (from_index % columns, (from_index / columns).floor())
+                        };
+                        let distance_x = from_x - index % columns;
+                        let distance_y = from_y - (index / columns).floor();
+                        match self.axis {
+                            Some(StaggerGridAxis::X) => -distance_x,
+                            Some(StaggerGridAxis::Y) => -distance_y,
+                            None => (distance_x * distance_x + distance_y * distance_y).sqrt(),
+                        }
+                    }
+                }
+            })
+            .collect();
+        let max = values.iter().copied().fold(0.0, f32::max);
+
+        if let Some(easing) = &self.easing {
+            if max != 0.0 {
+                for value in values.iter_mut() {
+                    *value = easing.at(*value / max) * max;
+                }
+            }
+        }
+
+        if self.direction == Some(StaggerDirection::Reverse) {
+            for value in values.iter_mut() {
+                *value = if self.axis.is_some() {
+                    -*value
+                } else {
+                    (max - *value).abs()
+                };
+            }
+        }
+
+        (values, max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::easings::Direction;
+
+    /// The staggered value for each of `total` targets
+    fn values<T: Staggerable, S: Into<Stagger<T>>>(
+        value: S,
+        props: StaggerProps,
+        total: usize,
+    ) -> Vec<T> {
+        let f = stagger(value, props);
+        let target = Target::Selector("div");
+        (0..total).map(|index| f(&target, index, total)).collect()
+    }
+
+    #[test]
+    fn staggers_values() {
+        let props = StaggerProps::new;
+        assert_eq!(values(100, props(), 5), [0, 100, 200, 300, 400]);
+        assert_eq!(values(100, props().start(500.0), 3), [500, 600, 700]);
+        assert_eq!(
+            values(100, props().from(StaggerFrom::Last), 5),
+            [400, 300, 200, 100, 0]
+        );
+        assert_eq!(
+            values(100, props().from(StaggerFrom::Center), 5),
+            [200, 100, 0, 100, 200]
+        );
+        assert_eq!(
+            values(100, props().from(StaggerFrom::Center), 4),
+            [150, 50, 50, 150]
+        );
+        assert_eq!(
+            values(100, props().from(StaggerFrom::Index(1)), 5),
+            [100, 0, 100, 200, 300]
+        );
+        assert_eq!(
+            values(100, props().direction(StaggerDirection::Reverse), 5),
+            [400, 300, 200, 100, 0]
+        );
+    }
+
+    #[test]
+    fn staggers_ranges() {
+        let props = StaggerProps::new;
+        assert_eq!(
+            values(Stagger::Range(0.0, 100.0), props(), 5),
+            [0.0, 25.0, 50.0, 75.0, 100.0]
+        );
+        assert_eq!(
+            values(
+                Stagger::Range(0.0, 100.0),
+                props().from(StaggerFrom::Center),
+                5
+            ),
+            [100.0, 50.0, 0.0, 50.0, 100.0]
+        );
+        assert_eq!(
+            values(
+                Stagger::Range(0.0, 100.0),
+                props().easing(Easing::Quad(Direction::In)),
+                5
+            ),
+            [0.0, 6.25, 25.0, 56.25, 100.0]
+        );
+        // A single target has nothing to spread across
+        assert_eq!(values(Stagger::Range(10.0, 100.0), props(), 1), [10.0]);
+    }
+
+    #[test]
+    fn staggers_grids() {
+        let props = || StaggerProps::new().grid(3, 3);
+        assert_eq!(
+            values(100, props().from(StaggerFrom::Center), 9),
+            [141, 100, 141, 100, 0, 100, 141, 100, 141]
+        );
+        assert_eq!(
+            values(100, props().from(StaggerFrom::Index(0)), 9),
+            [0, 100, 200, 100, 141, 224, 200, 224, 283]
+        );
+        assert_eq!(
+            values(
+                100,
+                props().from(StaggerFrom::Center).axis(StaggerGridAxis::X),
+                9
+            ),
+            [-100, 0, 100, -100, 0, 100, -100, 0, 100]
+        );
+        assert_eq!(
+            values(
+                100,
+                props().from(StaggerFrom::Center).axis(StaggerGridAxis::Y),
+                9
+            ),
+            [-100, -100, -100, 0, 0, 0, 100, 100, 100]
+        );
+        assert_eq!(
+            values(
+                100,
+                props()
+                    .from(StaggerFrom::Center)
+                    .axis(StaggerGridAxis::Y)
+                    .direction(StaggerDirection::Reverse),
+                9
+            ),
+            [100, 100, 100, 0, 0, 0, -100, -100, -100]
+        );
+    }
+
+    #[test]
+    fn eases_distances_from_the_center() {
+        let eased = || StaggerProps::new().easing(Easing::Quad(Direction::In));
+        // Closer targets are bunched together, further ones spread out
+        assert_eq!(
+            values(100, eased().from(StaggerFrom::Center), 5),
+            [200, 50, 0, 50, 200]
+        );
+        assert_eq!(
+            values(100, eased().from(StaggerFrom::Center).grid(3, 3), 9),
+            [141, 71, 141, 71, 0, 71, 141, 71, 141]
+        );
+    }
+
+    #[test]
+    fn keeps_units() {
+        let staggered = values(Value::new(10.0, Some(Unit::Px)), StaggerProps::new(), 3);
+        let staggered: Vec<_> = staggered.iter().map(ToString::to_string).collect();
+        assert_eq!(staggered, ["0px", "10px", "20px"]);
+    }
+}
@@ -1,5 +1,5 @@
-use wasm_bindgen::{JsCast, JsValue, UnwrapThrowExt};
-use web_sys::{Element, HtmlCollection, HtmlElement, Node, NodeList};
+use wasm_bindgen::JsCast;
+use web_sys::HtmlElement;
 
 // fn select(selectors: &str) -> web_sys::NodeList {
 //     crate::util::document().query_selector_all(s)
@@ -24,7 +24,7 @@ impl From<&'static str> for Target {
 }
 
 impl Target {
-    fn list(&self) -> Result<Vec<HtmlElement>, TargetError> {
+    pub(crate) fn list(&self) -> Result<Vec<HtmlElement>, TargetError> {
         match self {
             Target::Selector(s) => {
                 let node_list = crate::util::document()
@@ -63,7 +63,7 @@ impl Target {
         for el in self.list()? {
             el.style()
                 .set_property(property, value)
-                .map(|_| TargetError::Css);
+                .map_err(|_| TargetError::Css)?;
         }
         Ok(())
     }
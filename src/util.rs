@@ -12,12 +12,85 @@ pub fn document_is_hidden() -> bool {
     window().document().map(|d| d.hidden()).unwrap_or_default()
 }
 
-pub fn get_element() {
-    document();
+/// Split a `transform` value into its functions' names and arguments, in
+/// order. Arguments are kept verbatim, so may have functions of their own
+/// (e.g. `translateX(calc(10px + 5%))`). Anything after an unclosed
+/// parenthesis is dropped
+pub fn parse_transforms(transform: &str) -> Vec<(String, String)> {
+    let mut functions = Vec::new();
+    let mut rest = transform.trim_start();
+    while let Some(open) = rest.find('(') {
+        let mut depth = 0;
+        let close = rest[open..].char_indices().find_map(|(i, c)| {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => return None,
+            }
+            Some(open + i).filter(|_| depth == 0)
+        });
+        let close = match close {
+            Some(close) => close,
+            None => break,
+        };
+        functions.push((
+            rest[..open].trim().to_string(),
+            rest[open + 1..close].trim().to_string(),
+        ));
+        rest = rest[close + 1..].trim_start();
+    }
+    functions
 }
 
 // TODO: replace with gloo call
-pub fn request_animation_frame<F: 'static + FnMut(i32)>(f: F) -> Result<i32, JsValue> {
-    let closure = Closure::wrap(Box::new(f) as Box<dyn FnMut(i32)>);
-    window().request_animation_frame(closure.as_ref().unchecked_ref())
+pub fn request_animation_frame<F: 'static + FnOnce(f64)>(f: F) -> Result<i32, JsValue> {
+    // The closure is owned by the JS side, and dropped after it's called once
+    let closure = Closure::once_into_js(f);
+    window().request_animation_frame(closure.unchecked_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn function(name: &str, args: &str) -> (String, String) {
+        (name.to_string(), args.to_string())
+    }
+
+    #[test]
+    fn parses_transforms() {
+        assert_eq!(
+            parse_transforms(" translateX(10px)  rotate( 45deg ) scale(1.5, 2)"),
+            [
+                function("translateX", "10px"),
+                function("rotate", "45deg"),
+                function("scale", "1.5, 2"),
+            ]
+        );
+        assert_eq!(parse_transforms("none"), []);
+        assert_eq!(parse_transforms(""), []);
+    }
+
+    #[test]
+    fn keeps_nested_functions() {
+        assert_eq!(
+            parse_transforms("translateX(calc(10px + (5% * 2))) rotate(var(--angle))"),
+            [
+                function("translateX", "calc(10px + (5% * 2))"),
+                function("rotate", "var(--angle)"),
+            ]
+        );
+        assert_eq!(
+            parse_transforms("var(--transform) scale(2)"),
+            [function("var", "--transform"), function("scale", "2")]
+        );
+    }
+
+    #[test]
+    fn drops_unclosed_functions() {
+        assert_eq!(
+            parse_transforms("scale(2) translateX(calc(10px)"),
+            [function("scale", "2")]
+        );
+    }
 }